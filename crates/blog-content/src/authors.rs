@@ -0,0 +1,255 @@
+//! Author profiles loaded from the content directory
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::Post;
+use crate::parser::{load_data_file, ContentError};
+
+/// A link shown on an author's profile
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuthorLink {
+    pub label: String,
+    pub url: String,
+}
+
+/// An author profile, as defined in the content's `authors.yaml`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Author {
+    /// Identifier used in URLs, e.g. `/authors/gudnuf`
+    pub id: String,
+    /// Name shown in bylines
+    pub name: String,
+    #[serde(default)]
+    pub bio: Option<String>,
+    /// Avatar image URL or path
+    #[serde(default)]
+    pub avatar: Option<String>,
+    #[serde(default)]
+    pub links: Vec<AuthorLink>,
+    /// Template used to render this author's post cards on the index
+    #[serde(default = "default_card_template")]
+    pub card_template: String,
+    /// Short label shown beside the name in the author's badge, such as
+    /// `AI` or `Human`
+    #[serde(default)]
+    pub kind: Option<String>,
+    /// CSS color identifying the author, used to accent their badge, post
+    /// cards and generated preview images
+    #[serde(default)]
+    pub color: Option<String>,
+    /// Whether the author has a profile, as opposed to a name that only
+    /// appears in post frontmatter
+    #[serde(skip_deserializing, default = "registered")]
+    pub registered: bool,
+}
+
+fn default_card_template() -> String {
+    "partials/post_card.html".to_string()
+}
+
+fn registered() -> bool {
+    true
+}
+
+impl Author {
+    /// Byline for an author name that has no profile
+    pub fn unregistered(name: &str) -> Self {
+        Self {
+            id: name.to_string(),
+            name: name.to_string(),
+            bio: None,
+            avatar: None,
+            links: Vec::new(),
+            card_template: default_card_template(),
+            kind: None,
+            color: None,
+            registered: false,
+        }
+    }
+}
+
+/// Every author with a profile, in display order
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Authors {
+    authors: Vec<Author>,
+}
+
+impl Authors {
+    /// Name of the author profiles file in the content directory
+    pub const FILE_NAME: &'static str = "authors.yaml";
+
+    pub fn new(authors: Vec<Author>) -> Self {
+        Self { authors }
+    }
+
+    /// Load author profiles from `content_dir/authors.yaml`
+    ///
+    /// A missing file yields an empty set, so every author is shown by the
+    /// name in their posts' frontmatter without a profile.
+    pub fn load(content_dir: &Path) -> Result<Self, ContentError> {
+        let authors: Self = load_data_file(content_dir, Self::FILE_NAME)?;
+
+        for (i, author) in authors.authors.iter().enumerate() {
+            if authors.authors[..i].iter().any(|a| a.id == author.id) {
+                tracing::warn!("Author {:?} is defined more than once", author.id);
+            }
+        }
+
+        Ok(authors)
+    }
+
+    /// Look up an author by id
+    pub fn get(&self, id: &str) -> Option<&Author> {
+        self.authors.iter().find(|a| a.id == id)
+    }
+
+    /// Find the profile a frontmatter `author` refers to, by id or by name
+    ///
+    /// Names are compared case-insensitively, so `author: "Claude"` finds the
+    /// profile with id `claude`.
+    pub fn find(&self, id_or_name: &str) -> Option<&Author> {
+        self.get(id_or_name).or_else(|| {
+            self.authors.iter().find(|a| {
+                a.id.eq_ignore_ascii_case(id_or_name) || a.name.eq_ignore_ascii_case(id_or_name)
+            })
+        })
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Author> {
+        self.authors.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.authors.is_empty()
+    }
+
+    /// Attach author profiles to each post's bylines, in frontmatter order
+    ///
+    /// Names without a profile are kept as unregistered bylines and logged.
+    pub fn resolve(&self, posts: &mut [Post]) {
+        self.resolve_with_default(posts, None);
+    }
+
+    /// Like [`Authors::resolve`], crediting `default` on posts whose
+    /// frontmatter names no author
+    pub fn resolve_with_default(&self, posts: &mut [Post], default: Option<&str>) {
+        for post in posts {
+            let names: Vec<&str> = match post.frontmatter.authors.as_slice() {
+                [] => default.into_iter().collect(),
+                names => names.iter().map(String::as_str).collect(),
+            };
+            let mut authors: Vec<Author> = Vec::with_capacity(names.len());
+
+            for name in names {
+                let author = match self.find(name) {
+                    Some(author) => author.clone(),
+                    None => {
+                        if !self.is_empty() {
+                            tracing::warn!(
+                                "Post {:?} has author {:?} with no profile in {}",
+                                post.slug(),
+                                name,
+                                Self::FILE_NAME
+                            );
+                        }
+                        Author::unregistered(name)
+                    }
+                };
+
+                // `[claude, Claude]` names the same profile twice
+                if !authors.iter().any(|a| a.id == author.id) {
+                    authors.push(author);
+                }
+            }
+
+            post.authors = authors;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Frontmatter;
+
+    fn post_by(author: &str) -> Post {
+        let yaml = format!(
+            "title: \"Test\"\nslug: \"test\"\ndate: 2025-01-01\nauthor: \"{}\"\n",
+            author
+        );
+        let frontmatter: Frontmatter = serde_yaml::from_str(&yaml).unwrap();
+        Post::new(frontmatter, String::new(), "test.md".to_string())
+    }
+
+    #[test]
+    fn test_load_and_resolve_authors() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join(Authors::FILE_NAME),
+            "- id: claude\n  name: Claude\n  bio: An AI\n  links:\n    - label: Site\n      url: https://example.com\n  card_template: partials/post_card_claude.html\n  kind: AI\n  color: \"#3b82f6\"\n- id: gudnuf\n  name: gudnuf\n",
+        )
+        .unwrap();
+        let authors = Authors::load(dir.path()).unwrap();
+
+        let claude = authors.get("claude").unwrap();
+        assert!(claude.registered);
+        assert_eq!(claude.links[0].url, "https://example.com");
+        assert_eq!(claude.card_template, "partials/post_card_claude.html");
+        assert_eq!(claude.kind.as_deref(), Some("AI"));
+        assert_eq!(claude.color.as_deref(), Some("#3b82f6"));
+        assert_eq!(
+            authors.get("gudnuf").unwrap().card_template,
+            "partials/post_card.html"
+        );
+
+        let mut posts = vec![post_by("Claude"), post_by("Someone Else")];
+        authors.resolve(&mut posts);
+        assert_eq!(posts[0].authors[0].id, "claude");
+        assert_eq!(posts[1].authors[0].name, "Someone Else");
+        assert!(!posts[1].authors[0].registered);
+    }
+
+    #[test]
+    fn test_resolve_co_authors() {
+        let authors: Authors =
+            serde_yaml::from_str("- id: claude\n  name: Claude\n- id: gudnuf\n  name: gudnuf\n")
+                .unwrap();
+
+        let mut post = post_by("gudnuf");
+        post.frontmatter.authors = vec![
+            "gudnuf".to_string(),
+            "Claude".to_string(),
+            "claude".to_string(),
+            "A Guest".to_string(),
+        ];
+        let mut posts = vec![post];
+        authors.resolve(&mut posts);
+
+        let ids: Vec<_> = posts[0].authors.iter().map(|a| a.id.as_str()).collect();
+        assert_eq!(ids, vec!["gudnuf", "claude", "A Guest"]);
+        assert!(!posts[0].authors[2].registered);
+    }
+
+    #[test]
+    fn test_resolve_with_default_author() {
+        let authors: Authors = serde_yaml::from_str("- id: claude\n  name: Claude\n").unwrap();
+
+        let mut unsigned = post_by("gudnuf");
+        unsigned.frontmatter.authors.clear();
+        let mut posts = vec![unsigned, post_by("gudnuf")];
+        authors.resolve_with_default(&mut posts, Some("Claude"));
+
+        assert_eq!(posts[0].authors[0].id, "claude");
+        assert!(posts[0].frontmatter.authors.is_empty());
+        assert_eq!(posts[1].authors, vec![Author::unregistered("gudnuf")]);
+    }
+
+    #[test]
+    fn test_new_post_has_unregistered_byline() {
+        let post = post_by("gudnuf");
+        assert_eq!(post.authors, vec![Author::unregistered("gudnuf")]);
+    }
+}
@@ -0,0 +1,301 @@
+//! Pre-rendered HTML cache keyed by content hash
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::{Post, RenderedContent};
+use crate::render::Renderer;
+
+/// Stable 64-bit FNV-1a hasher for content fingerprints
+///
+/// Unlike `std::collections::hash_map::DefaultHasher`, the output is the same
+/// across runs and Rust versions, so hashes can be persisted or sent as ETags.
+#[derive(Debug, Clone)]
+pub struct ContentHasher(u64);
+
+impl ContentHasher {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    pub fn new() -> Self {
+        Self(Self::OFFSET_BASIS)
+    }
+
+    /// Feed raw bytes into the hash
+    pub fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(Self::PRIME);
+        }
+    }
+
+    /// Feed a length-prefixed string, so `("ab", "c")` and `("a", "bc")` differ
+    pub fn write_str(&mut self, text: &str) {
+        self.write(&(text.len() as u64).to_le_bytes());
+        self.write(text.as_bytes());
+    }
+
+    pub fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+impl Default for ContentHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Hash of everything that affects a post's rendered output
+pub fn post_render_hash(post: &Post, renderer_fingerprint: u64) -> u64 {
+    let mut hasher = ContentHasher::new();
+    hasher.write(&renderer_fingerprint.to_le_bytes());
+    hasher.write(&[u8::from(post.frontmatter.toc)]);
+    hasher.write_str(&post.raw_content);
+    hasher.finish()
+}
+
+/// A cached rendering together with the hash it was computed from
+#[derive(Debug, Clone)]
+struct CacheEntry {
+    hash: u64,
+    rendered: Arc<RenderedContent>,
+}
+
+/// Counts reported by [`RenderCache::refresh`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RefreshStats {
+    /// Posts that were (re-)rendered
+    pub rendered: usize,
+    /// Posts whose cached HTML was still valid
+    pub reused: usize,
+    /// Entries dropped because their post no longer exists
+    pub removed: usize,
+}
+
+/// A serializable copy of a [`RenderCache`], for reuse by a later process
+///
+/// Static exports save one next to their output so that an unchanged post is
+/// not highlighted again on the next build.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RenderSnapshot {
+    fingerprint: u64,
+    entries: BTreeMap<String, SnapshotEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SnapshotEntry {
+    hash: u64,
+    rendered: RenderedContent,
+}
+
+/// Rendered HTML for every post, keyed by slug and validated by content hash
+///
+/// Cloning is cheap: rendered content is shared behind `Arc`s, so a cache can
+/// be cloned, refreshed off to the side and swapped in.
+#[derive(Clone)]
+pub struct RenderCache {
+    renderer: Arc<Renderer>,
+    fingerprint: u64,
+    entries: HashMap<String, CacheEntry>,
+}
+
+impl RenderCache {
+    /// Create an empty cache that renders with the given renderer
+    pub fn new(renderer: Arc<Renderer>) -> Self {
+        let fingerprint = renderer.fingerprint();
+        Self {
+            renderer,
+            fingerprint,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// The renderer used for cache misses
+    pub fn renderer(&self) -> &Arc<Renderer> {
+        &self.renderer
+    }
+
+    /// Bring the cache in line with `posts`, rendering only what changed
+    pub fn refresh(&mut self, posts: &[Post]) -> RefreshStats {
+        let mut stats = RefreshStats::default();
+        let mut entries = HashMap::with_capacity(posts.len());
+
+        for post in posts {
+            let hash = post_render_hash(post, self.fingerprint);
+
+            let entry = match self.entries.remove(post.slug()) {
+                Some(entry) if entry.hash == hash => {
+                    stats.reused += 1;
+                    entry
+                }
+                _ => {
+                    stats.rendered += 1;
+                    CacheEntry {
+                        hash,
+                        rendered: Arc::new(self.renderer.render_post(post)),
+                    }
+                }
+            };
+
+            entries.insert(post.slug().to_string(), entry);
+        }
+
+        stats.removed = self.entries.len();
+        self.entries = entries;
+
+        stats
+    }
+
+    /// Cached rendering for a post, if it is present and up to date
+    pub fn get(&self, post: &Post) -> Option<Arc<RenderedContent>> {
+        self.entries
+            .get(post.slug())
+            .filter(|entry| entry.hash == post_render_hash(post, self.fingerprint))
+            .map(|entry| Arc::clone(&entry.rendered))
+    }
+
+    /// Cached rendering for a post, rendering it on a miss without caching
+    pub fn get_or_render(&self, post: &Post) -> Arc<RenderedContent> {
+        self.get(post).unwrap_or_else(|| {
+            tracing::debug!("Render cache miss for {}", post.slug());
+            Arc::new(self.renderer.render_post(post))
+        })
+    }
+
+    /// Copy every cached rendering out for persisting
+    pub fn snapshot(&self) -> RenderSnapshot {
+        let entries = self
+            .entries
+            .iter()
+            .map(|(slug, entry)| {
+                let entry = SnapshotEntry {
+                    hash: entry.hash,
+                    rendered: RenderedContent::clone(&entry.rendered),
+                };
+                (slug.clone(), entry)
+            })
+            .collect();
+
+        RenderSnapshot {
+            fingerprint: self.fingerprint,
+            entries,
+        }
+    }
+
+    /// Seed the cache from a snapshot, returning how many entries were taken
+    ///
+    /// A snapshot made by a renderer with a different fingerprint is ignored.
+    /// Entries are still checked against each post's hash on the next
+    /// [`RenderCache::refresh`], so stale ones are simply re-rendered.
+    pub fn restore(&mut self, snapshot: RenderSnapshot) -> usize {
+        if snapshot.fingerprint != self.fingerprint {
+            return 0;
+        }
+
+        let restored = snapshot.entries.len();
+        self.entries
+            .extend(snapshot.entries.into_iter().map(|(slug, entry)| {
+                let entry = CacheEntry {
+                    hash: entry.hash,
+                    rendered: Arc::new(entry.rendered),
+                };
+                (slug, entry)
+            }));
+
+        restored
+    }
+
+    /// Number of cached posts
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the cache holds no posts
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Frontmatter;
+
+    fn test_post(slug: &str, content: &str) -> Post {
+        let yaml = format!("title: \"{}\"\nslug: \"{}\"\ndate: 2025-01-01\n", slug, slug);
+        let frontmatter: Frontmatter = serde_yaml::from_str(&yaml).unwrap();
+        Post::new(frontmatter, content.to_string(), format!("{}.md", slug))
+    }
+
+    #[test]
+    fn test_content_hasher_is_stable() {
+        let mut hasher = ContentHasher::new();
+        hasher.write(b"hello");
+        // Reference FNV-1a 64 value for "hello"
+        assert_eq!(hasher.finish(), 0xa430_d846_80aa_bd0b);
+    }
+
+    #[test]
+    fn test_refresh_only_renders_changed_posts() {
+        let mut cache = RenderCache::new(Arc::new(Renderer::default()));
+        let mut posts = vec![test_post("one", "# One"), test_post("two", "# Two")];
+
+        let stats = cache.refresh(&posts);
+        assert_eq!(stats.rendered, 2);
+        assert_eq!(cache.len(), 2);
+
+        let before = cache.get(&posts[0]).unwrap();
+        posts[1].raw_content = "# Two, edited".to_string();
+        posts.push(test_post("three", "# Three"));
+
+        let stats = cache.refresh(&posts);
+        assert_eq!(
+            stats,
+            RefreshStats {
+                rendered: 2,
+                reused: 1,
+                removed: 0
+            }
+        );
+        assert!(Arc::ptr_eq(&before, &cache.get(&posts[0]).unwrap()));
+        assert!(cache.get(&posts[1]).unwrap().html.contains("Two, edited"));
+
+        let stats = cache.refresh(&posts[..1]);
+        assert_eq!(stats.removed, 2);
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_stale_entries_are_not_served() {
+        let mut cache = RenderCache::new(Arc::new(Renderer::default()));
+        let mut post = test_post("one", "Original");
+        cache.refresh(std::slice::from_ref(&post));
+
+        post.raw_content = "Changed".to_string();
+        assert!(cache.get(&post).is_none());
+        assert!(cache.get_or_render(&post).html.contains("Changed"));
+    }
+
+    #[test]
+    fn test_restore_from_snapshot() {
+        let posts = vec![test_post("one", "# One"), test_post("two", "# Two")];
+        let mut cache = RenderCache::new(Arc::new(Renderer::default()));
+        cache.refresh(&posts);
+        let snapshot = cache.snapshot();
+
+        let mut restored = RenderCache::new(Arc::new(Renderer::default()));
+        assert_eq!(restored.restore(snapshot.clone()), 2);
+        let stats = restored.refresh(&posts);
+        assert_eq!(stats.rendered, 0);
+        assert_eq!(stats.reused, 2);
+
+        let mut other_renderer = snapshot;
+        other_renderer.fingerprint ^= 1;
+        let mut ignored = RenderCache::new(Arc::new(Renderer::default()));
+        assert_eq!(ignored.restore(other_renderer), 0);
+        assert!(ignored.is_empty());
+    }
+}
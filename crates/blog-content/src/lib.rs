@@ -3,10 +3,32 @@
 //! This crate provides functionality for parsing markdown blog posts and pages
 //! with YAML frontmatter, syntax highlighting, and table of contents generation.
 
+pub mod authors;
+pub mod cache;
 pub mod highlighter;
 pub mod models;
 pub mod parser;
+pub mod registry;
+pub mod render;
+pub mod search;
+pub mod series;
+pub mod stats;
+pub mod summary;
+pub mod taxonomy;
+pub mod timeline;
 pub mod toc;
 
-pub use models::{category_display_name, Frontmatter, Page, Post, RenderedContent, CATEGORIES};
-pub use parser::{load_all_posts, load_page, load_post, ContentError};
+pub use authors::{Author, AuthorLink, Authors};
+pub use cache::{RenderCache, RenderSnapshot, RefreshStats};
+pub use models::{Frontmatter, Page, Post, PostLink, RenderedContent};
+pub use parser::{
+    load_all_pages, load_all_posts, load_page, load_post, reload_changed_posts, ContentError,
+};
+pub use registry::Registry;
+pub use render::{EventTransform, RenderContext, RenderOptions, Renderer};
+pub use search::{excerpt, plain_text, ClientDoc, ClientHeading, ClientIndex, SearchHit, SearchIndex};
+pub use series::{Series, SeriesIndex, SeriesNav, SeriesRef};
+pub use stats::{ReadingStats, WORDS_PER_MINUTE};
+pub use summary::{summary_html, MORE_MARKER, SUMMARY_WORDS};
+pub use taxonomy::{normalize_tag, tag_counts, Categories, Category, TagCount};
+pub use timeline::{Adjacent, Timeline, TimelineScope};
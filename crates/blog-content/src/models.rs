@@ -3,24 +3,11 @@
 use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
 use serde::{Deserialize, Deserializer, Serialize};
 
-/// All valid categories for posts - defined in one place
-pub const CATEGORIES: &[(&str, &str)] = &[
-    ("engineering", "Engineering"),
-    ("programming", "Programming"),
-    ("devops", "DevOps"),
-    ("web-development", "Web Development"),
-    ("reflections", "Reflections"),
-    ("reference", "Reference"),
-];
-
-/// Get the display name for a category slug
-pub fn category_display_name(slug: &str) -> &str {
-    CATEGORIES
-        .iter()
-        .find(|(s, _)| *s == slug)
-        .map(|(_, name)| *name)
-        .unwrap_or(slug)
-}
+use crate::authors::Author;
+use crate::series::SeriesRef;
+use crate::stats::ReadingStats;
+use crate::summary::{summary_html, SUMMARY_WORDS};
+use crate::taxonomy::normalize_tag;
 
 /// Custom deserializer that handles both date and datetime formats
 fn deserialize_datetime<'de, D>(deserializer: D) -> Result<NaiveDateTime, D::Error>
@@ -48,6 +35,62 @@ where
     )))
 }
 
+/// Optional variant of [`deserialize_datetime`], for fields that may be absent
+fn deserialize_optional_datetime<'de, D>(deserializer: D) -> Result<Option<NaiveDateTime>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    struct Wrapper(#[serde(deserialize_with = "deserialize_datetime")] NaiveDateTime);
+
+    let wrapper: Option<Wrapper> = Deserialize::deserialize(deserializer)?;
+    Ok(wrapper.map(|Wrapper(dt)| dt))
+}
+
+/// Custom deserializer that normalizes tags and drops duplicates and blanks
+fn deserialize_tags<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let raw: Vec<String> = Deserialize::deserialize(deserializer)?;
+
+    let mut tags: Vec<String> = Vec::with_capacity(raw.len());
+    for tag in raw.iter().map(|t| normalize_tag(t)) {
+        if !tag.is_empty() && !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+
+    Ok(tags)
+}
+
+/// Custom deserializer that accepts a single author or a list of co-authors
+fn deserialize_authors<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    let raw = match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(author) => vec![author],
+        OneOrMany::Many(authors) => authors,
+    };
+
+    let mut authors: Vec<String> = Vec::with_capacity(raw.len());
+    for author in raw.iter().map(|a| a.trim()) {
+        if !author.is_empty() && !authors.iter().any(|a| a == author) {
+            authors.push(author.to_string());
+        }
+    }
+
+    Ok(authors)
+}
+
 /// Custom serializer for datetime that outputs in readable format
 fn serialize_datetime<S>(dt: &NaiveDateTime, serializer: S) -> Result<S::Ok, S::Error>
 where
@@ -56,22 +99,30 @@ where
     serializer.serialize_str(&dt.format("%Y-%m-%d %H:%M:%S").to_string())
 }
 
+fn serialize_optional_datetime<S>(
+    dt: &Option<NaiveDateTime>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    match dt {
+        Some(dt) => serialize_datetime(dt, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
 /// Relationship type between posts
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum RelationType {
+    #[default]
     Related,
     Sequel,
     Prequel,
     Conversation,
 }
 
-impl Default for RelationType {
-    fn default() -> Self {
-        RelationType::Related
-    }
-}
-
 impl RelationType {
     pub fn label(&self) -> &str {
         match self {
@@ -100,11 +151,30 @@ pub struct Frontmatter {
     pub date: NaiveDateTime,
     #[serde(default)]
     pub updated: Option<NaiveDate>,
-    #[serde(default)]
+    /// When the post goes live, if later than `date`; posts are hidden until
+    /// both have passed, in the site's timezone
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_datetime",
+        serialize_with = "serialize_optional_datetime"
+    )]
+    pub publish_at: Option<NaiveDateTime>,
+    /// Author ids or names; `author: "gudnuf"` and `author: [claude, gudnuf]`
+    /// are both accepted
+    #[serde(
+        default,
+        rename = "author",
+        alias = "authors",
+        deserialize_with = "deserialize_authors"
+    )]
+    pub authors: Vec<String>,
+    /// The first of `authors`, filled in by [`Post::new`]; kept for code
+    /// written before posts could have co-authors
+    #[serde(skip)]
     pub author: Option<String>,
     #[serde(default)]
     pub description: Option<String>,
-    #[serde(default)]
+    #[serde(default, deserialize_with = "deserialize_tags")]
     pub tags: Vec<String>,
     #[serde(default)]
     pub category: Option<String>,
@@ -118,6 +188,8 @@ pub struct Frontmatter {
     pub featured_image: Option<String>,
     #[serde(default)]
     pub related_posts: Vec<RelatedPost>,
+    #[serde(default)]
+    pub series: Option<SeriesRef>,
 }
 
 fn default_template() -> String {
@@ -130,9 +202,37 @@ pub struct Post {
     pub frontmatter: Frontmatter,
     pub raw_content: String,
     pub file_path: String,
+    /// Bylines, resolved against the author registry when posts are loaded
+    pub authors: Vec<Author>,
+    /// Word count, reading time and code block count of the body
+    pub stats: ReadingStats,
+    /// Opening of the post as balanced HTML, for listings and feeds; see
+    /// [`crate::summary_html`]
+    pub summary_html: String,
 }
 
 impl Post {
+    /// Create a post whose bylines are taken verbatim from the frontmatter
+    ///
+    /// Use [`crate::Authors::resolve`] to attach author profiles.
+    pub fn new(mut frontmatter: Frontmatter, raw_content: String, file_path: String) -> Self {
+        frontmatter.author = frontmatter.authors.first().cloned();
+        let authors = frontmatter
+            .authors
+            .iter()
+            .map(|name| Author::unregistered(name))
+            .collect();
+
+        Self {
+            stats: ReadingStats::from_markdown(&raw_content),
+            summary_html: summary_html(&raw_content, SUMMARY_WORDS),
+            frontmatter,
+            raw_content,
+            file_path,
+            authors,
+        }
+    }
+
     /// Get the post's title
     pub fn title(&self) -> &str {
         &self.frontmatter.title
@@ -148,16 +248,34 @@ impl Post {
         self.frontmatter.date
     }
 
+    /// When the post becomes visible: its `date`, or `publish_at` if later
+    pub fn publish_time(&self) -> NaiveDateTime {
+        self.frontmatter
+            .publish_at
+            .map_or(self.frontmatter.date, |at| at.max(self.frontmatter.date))
+    }
+
+    /// Check if the post is still waiting for its publish time at `now`
+    pub fn is_scheduled_at(&self, now: NaiveDateTime) -> bool {
+        self.publish_time() > now
+    }
+
     /// Check if the post is a draft
     pub fn is_draft(&self) -> bool {
         self.frontmatter.draft
     }
 
-    /// Get the post's author
+    /// Get the post's first author
     pub fn author(&self) -> Option<&str> {
         self.frontmatter.author.as_deref()
     }
 
+    /// Check whether the post has a tag, comparing normalized forms
+    pub fn has_tag(&self, tag: &str) -> bool {
+        let tag = normalize_tag(tag);
+        self.frontmatter.tags.contains(&tag)
+    }
+
     /// Get explicitly related posts (from frontmatter)
     pub fn related_posts(&self) -> &[RelatedPost] {
         &self.frontmatter.related_posts
@@ -185,7 +303,7 @@ impl Post {
             .filter(|(_, count)| *count > 0)
             .collect();
 
-        similar.sort_by(|a, b| b.1.cmp(&a.1));
+        similar.sort_by_key(|s| std::cmp::Reverse(s.1));
 
         similar
             .into_iter()
@@ -195,6 +313,24 @@ impl Post {
     }
 }
 
+/// Link to a post, as shown in navigation between posts
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PostLink {
+    pub slug: String,
+    pub title: String,
+    pub date: NaiveDateTime,
+}
+
+impl From<&Post> for PostLink {
+    fn from(post: &Post) -> Self {
+        Self {
+            slug: post.slug().to_string(),
+            title: post.title().to_string(),
+            date: post.date(),
+        }
+    }
+}
+
 /// Frontmatter for static pages (simpler than posts)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PageFrontmatter {
@@ -202,6 +338,8 @@ pub struct PageFrontmatter {
     pub slug: String,
     #[serde(default = "default_page_template")]
     pub template: String,
+    #[serde(default)]
+    pub toc: bool,
 }
 
 fn default_page_template() -> String {
@@ -214,12 +352,13 @@ pub struct Page {
     pub title: String,
     pub slug: String,
     pub template: String,
+    pub toc: bool,
     pub raw_content: String,
     pub file_path: String,
 }
 
 /// Rendered markdown content with optional table of contents
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RenderedContent {
     pub html: String,
     pub toc: Option<String>,
@@ -1,9 +1,10 @@
 //! Content parsing functionality
 
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use gray_matter::{engine::YAML, Matter};
+use serde::de::DeserializeOwned;
 use thiserror::Error;
 use walkdir::WalkDir;
 
@@ -29,6 +30,9 @@ pub enum ContentError {
 
     #[error("Invalid content path: {0}")]
     InvalidPath(String),
+
+    #[error("Failed to parse data file {0}: {1}")]
+    DataParse(String, String),
 }
 
 /// Parse a single post from a file path
@@ -44,11 +48,30 @@ pub fn load_post(path: &Path) -> Result<Post, ContentError> {
         .deserialize()
         .map_err(|e| ContentError::FrontmatterParse(e.to_string()))?;
 
-    Ok(Post {
+    Ok(Post::new(
         frontmatter,
-        raw_content: parsed.content,
-        file_path: path.to_string_lossy().to_string(),
-    })
+        parsed.content,
+        path.to_string_lossy().to_string(),
+    ))
+}
+
+/// Load a YAML data file such as `categories.yaml` from the content directory
+///
+/// A missing file is logged and yields the default value.
+pub(crate) fn load_data_file<T>(content_dir: &Path, file_name: &str) -> Result<T, ContentError>
+where
+    T: DeserializeOwned + Default,
+{
+    let path = content_dir.join(file_name);
+
+    if !path.exists() {
+        tracing::warn!("No data file found at {:?}", path);
+        return Ok(T::default());
+    }
+
+    let content = fs::read_to_string(&path)?;
+    serde_yaml::from_str(&content)
+        .map_err(|e| ContentError::DataParse(path.to_string_lossy().to_string(), e.to_string()))
 }
 
 /// Load all posts from a content directory
@@ -72,7 +95,7 @@ pub fn load_all_posts(content_dir: &Path) -> Result<Vec<Post>, ContentError> {
     {
         let path = entry.path();
 
-        if path.extension().map_or(false, |ext| ext == "md") {
+        if path.extension().is_some_and(|ext| ext == "md") {
             match load_post(path) {
                 Ok(post) => posts.push(post),
                 Err(e) => {
@@ -83,11 +106,39 @@ pub fn load_all_posts(content_dir: &Path) -> Result<Vec<Post>, ContentError> {
     }
 
     // Sort by date, newest first
-    posts.sort_by(|a, b| b.date().cmp(&a.date()));
+    posts.sort_by_key(|p| std::cmp::Reverse(p.date()));
 
     Ok(posts)
 }
 
+/// Apply filesystem changes to an already-loaded set of posts
+///
+/// Each changed markdown file is re-read if it still exists and dropped from
+/// `posts` if it was removed, so only the touched files are parsed. Changed
+/// paths are matched against each post's `file_path`, so they must be given
+/// the way the posts were loaded, e.g. under the `content_dir` passed to
+/// [`load_all_posts`]. Files that fail to parse are logged and dropped, as in
+/// [`load_all_posts`]. Posts are re-sorted newest first afterwards.
+pub fn reload_changed_posts(posts: &mut Vec<Post>, changed: &[PathBuf]) {
+    for path in changed {
+        if path.extension().is_none_or(|ext| ext != "md") {
+            continue;
+        }
+        posts.retain(|p| Path::new(&p.file_path) != path);
+
+        if path.exists() {
+            match load_post(path) {
+                Ok(post) => posts.push(post),
+                Err(e) => {
+                    tracing::warn!("Failed to parse post {:?}: {}", path, e);
+                }
+            }
+        }
+    }
+
+    posts.sort_by_key(|p| std::cmp::Reverse(p.date()));
+}
+
 /// Load a specific post by slug
 pub fn load_post_by_slug(slug: &str, content_dir: &Path) -> Result<Post, ContentError> {
     let posts = load_all_posts(content_dir)?;
@@ -112,7 +163,48 @@ pub fn load_page(slug: &str, content_dir: &Path) -> Result<Page, ContentError> {
         return Err(ContentError::PageNotFound(slug.to_string()));
     }
 
-    let content = fs::read_to_string(&page_path)?;
+    parse_page(&page_path)
+}
+
+/// Load all static pages from a content directory
+///
+/// Pages are expected to be in `content_dir/pages/` and are returned sorted
+/// by slug. Pages that fail to parse are logged and skipped.
+pub fn load_all_pages(content_dir: &Path) -> Result<Vec<Page>, ContentError> {
+    let pages_dir = content_dir.join("pages");
+
+    if !pages_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut pages = Vec::new();
+
+    for entry in WalkDir::new(&pages_dir)
+        .min_depth(1)
+        .max_depth(1)
+        .into_iter()
+        .filter_map(|e| e.ok())
+    {
+        let path = entry.path();
+
+        if path.extension().is_some_and(|ext| ext == "md") {
+            match parse_page(path) {
+                Ok(page) => pages.push(page),
+                Err(e) => {
+                    tracing::warn!("Failed to parse page {:?}: {}", path, e);
+                }
+            }
+        }
+    }
+
+    pages.sort_by(|a, b| a.slug.cmp(&b.slug));
+
+    Ok(pages)
+}
+
+/// Parse a single page from a file path
+fn parse_page(page_path: &Path) -> Result<Page, ContentError> {
+    let content = fs::read_to_string(page_path)?;
     let matter = Matter::<YAML>::new();
     let parsed = matter.parse(&content);
 
@@ -126,6 +218,7 @@ pub fn load_page(slug: &str, content_dir: &Path) -> Result<Page, ContentError> {
         title: frontmatter.title,
         slug: frontmatter.slug,
         template: frontmatter.template,
+        toc: frontmatter.toc,
         raw_content: parsed.content,
         file_path: page_path.to_string_lossy().to_string(),
     })
@@ -134,6 +227,7 @@ pub fn load_page(slug: &str, content_dir: &Path) -> Result<Page, ContentError> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::NaiveDateTime;
     use std::fs;
     use tempfile::TempDir;
 
@@ -168,6 +262,96 @@ This is a test post.
         assert_eq!(post.slug(), "test-post");
         assert_eq!(post.frontmatter.author, Some("Test Author".to_string()));
         assert_eq!(post.frontmatter.tags, vec!["rust", "test"]);
+        assert_eq!(post.frontmatter.authors, vec!["Test Author"]);
+    }
+
+    #[test]
+    fn test_parse_post_with_co_authors() {
+        let temp_dir = TempDir::new().unwrap();
+        let content = "---\ntitle: \"Duet\"\nslug: \"duet\"\ndate: 2025-01-15\nauthor: [\"claude\", \" gudnuf \", \"claude\"]\n---\n\nBody\n";
+        create_test_post(temp_dir.path(), "duet.md", content);
+
+        let posts = load_all_posts(temp_dir.path()).unwrap();
+        assert_eq!(posts[0].frontmatter.authors, vec!["claude", "gudnuf"]);
+        assert_eq!(posts[0].author(), Some("claude"));
+        assert_eq!(posts[0].authors.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_publish_at() {
+        let temp_dir = TempDir::new().unwrap();
+        let content = "---\ntitle: \"Later\"\nslug: \"later\"\ndate: 2025-01-15\npublish_at: 2025-02-01 09:30:00\n---\n\nBody\n";
+        create_test_post(temp_dir.path(), "later.md", content);
+
+        let posts = load_all_posts(temp_dir.path()).unwrap();
+        let post = &posts[0];
+        let at = |s| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap();
+        assert_eq!(post.publish_time(), at("2025-02-01 09:30:00"));
+        assert!(post.is_scheduled_at(at("2025-02-01 09:29:59")));
+        assert!(!post.is_scheduled_at(at("2025-02-01 09:30:00")));
+
+        // A `publish_at` before the date does not publish early
+        let mut early = post.clone();
+        early.frontmatter.publish_at = Some(at("2025-01-01 00:00:00"));
+        assert_eq!(early.publish_time(), at("2025-01-15 00:00:00"));
+
+        let yaml = serde_yaml::to_string(&post.frontmatter).unwrap();
+        assert!(yaml.contains("publish_at: 2025-02-01 09:30:00"), "{}", yaml);
+    }
+
+    #[test]
+    fn test_reload_changed_posts() {
+        let temp_dir = TempDir::new().unwrap();
+        let post = |slug: &str, date: &str| {
+            format!("---\ntitle: \"{}\"\nslug: \"{}\"\ndate: {}\n---\n\nBody\n", slug, slug, date)
+        };
+        create_test_post(temp_dir.path(), "a.md", &post("a", "2025-01-01"));
+        create_test_post(temp_dir.path(), "b.md", &post("b", "2025-01-02"));
+
+        let mut posts = load_all_posts(temp_dir.path()).unwrap();
+        let posts_dir = temp_dir.path().join("posts");
+
+        // Edit one post, add another and delete a third
+        create_test_post(temp_dir.path(), "a.md", &post("a-renamed", "2025-01-01"));
+        create_test_post(temp_dir.path(), "c.md", &post("c", "2025-01-03"));
+        fs::remove_file(posts_dir.join("b.md")).unwrap();
+
+        reload_changed_posts(
+            &mut posts,
+            &[
+                posts_dir.join("a.md"),
+                posts_dir.join("b.md"),
+                posts_dir.join("c.md"),
+                posts_dir.join("notes.txt"),
+            ],
+        );
+
+        let slugs: Vec<_> = posts.iter().map(|p| p.slug()).collect();
+        assert_eq!(slugs, vec!["c", "a-renamed"]);
+    }
+
+    #[test]
+    fn test_reload_matches_full_path() {
+        let temp_dir = TempDir::new().unwrap();
+        let post = |slug: &str| {
+            format!(
+                "---\ntitle: {}\nslug: {}\ndate: 2025-01-01\n---\n",
+                slug, slug
+            )
+        };
+        let (one, two) = (temp_dir.path().join("one"), temp_dir.path().join("two"));
+        create_test_post(&one, "post.md", &post("from-one"));
+        create_test_post(&two, "post.md", &post("from-two"));
+
+        let mut posts = load_all_posts(&one).unwrap();
+        posts.extend(load_all_posts(&two).unwrap());
+
+        // A file with the same name in another directory is left alone
+        fs::remove_file(one.join("posts/post.md")).unwrap();
+        reload_changed_posts(&mut posts, &[one.join("posts/post.md")]);
+
+        let slugs: Vec<_> = posts.iter().map(|p| p.slug()).collect();
+        assert_eq!(slugs, vec!["from-two"]);
     }
 
     #[test]
@@ -192,6 +376,30 @@ Some content here.
         assert_eq!(page.slug, "about");
     }
 
+    #[test]
+    fn test_load_all_pages() {
+        let temp_dir = TempDir::new().unwrap();
+        let pages_dir = temp_dir.path().join("pages");
+        fs::create_dir_all(&pages_dir).unwrap();
+
+        fs::write(
+            pages_dir.join("now.md"),
+            "---\ntitle: \"Now\"\nslug: \"now\"\n---\n\nWhat I'm doing now.\n",
+        )
+        .unwrap();
+        fs::write(
+            pages_dir.join("about.md"),
+            "---\ntitle: \"About\"\nslug: \"about\"\n---\n\nAbout.\n",
+        )
+        .unwrap();
+        fs::write(pages_dir.join("broken.md"), "no frontmatter here").unwrap();
+        fs::write(pages_dir.join("notes.txt"), "not markdown").unwrap();
+
+        let pages = load_all_pages(temp_dir.path()).unwrap();
+        let slugs: Vec<_> = pages.iter().map(|p| p.slug.as_str()).collect();
+        assert_eq!(slugs, vec!["about", "now"]);
+    }
+
     #[test]
     fn test_path_traversal_protection() {
         let temp_dir = TempDir::new().unwrap();
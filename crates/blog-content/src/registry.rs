@@ -0,0 +1,35 @@
+//! Site-wide data files that posts refer to
+
+use std::path::Path;
+
+use crate::authors::Authors;
+use crate::models::Post;
+use crate::parser::ContentError;
+use crate::taxonomy::Categories;
+
+/// Categories and author profiles loaded from the content directory
+#[derive(Debug, Clone, Default)]
+pub struct Registry {
+    pub categories: Categories,
+    pub authors: Authors,
+    /// Credited on posts whose frontmatter names no author
+    pub default_author: Option<String>,
+}
+
+impl Registry {
+    /// Load `categories.yaml` and `authors.yaml` from the content directory
+    pub fn load(content_dir: &Path) -> Result<Self, ContentError> {
+        Ok(Self {
+            categories: Categories::load(content_dir)?,
+            authors: Authors::load(content_dir)?,
+            default_author: None,
+        })
+    }
+
+    /// Resolve post bylines and warn about references to undefined entries
+    pub fn apply(&self, posts: &mut [Post]) {
+        self.authors
+            .resolve_with_default(posts, self.default_author.as_deref());
+        self.categories.warn_unknown(posts);
+    }
+}
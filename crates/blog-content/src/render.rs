@@ -0,0 +1,270 @@
+//! Markdown rendering pipeline
+//!
+//! Markdown is parsed into a stream of `pulldown_cmark` events, passed through
+//! an ordered list of [`EventTransform`]s, and written out as HTML. The default
+//! pipeline highlights fenced code blocks and anchors headings so they line up
+//! with the generated table of contents.
+
+use pulldown_cmark::{CodeBlockKind, CowStr, Event, Options, Parser, Tag, TagEnd};
+
+use crate::cache::ContentHasher;
+use crate::highlighter::highlight_code;
+use crate::models::{Page, Post, RenderedContent};
+use crate::toc::{anchor_headings, render_toc, TocEntry};
+
+/// Information collected about a document while it is being rendered
+#[derive(Debug, Default)]
+pub struct RenderContext {
+    /// Headings found by the heading anchor pass, in document order
+    pub toc_entries: Vec<TocEntry>,
+}
+
+/// A single step in the rendering pipeline
+pub trait EventTransform: Send + Sync {
+    /// Rewrite the event stream, optionally recording data in the context
+    fn transform<'a>(&self, events: Vec<Event<'a>>, context: &mut RenderContext) -> Vec<Event<'a>>;
+
+    /// Identifies this transform and its settings in the renderer fingerprint
+    ///
+    /// Transforms with configurable behaviour should include their settings so
+    /// that cached output is invalidated when they change.
+    fn fingerprint(&self) -> String {
+        std::any::type_name::<Self>().to_string()
+    }
+}
+
+/// Replaces fenced and indented code blocks with syntect-highlighted HTML
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SyntaxHighlighting;
+
+impl EventTransform for SyntaxHighlighting {
+    fn transform<'a>(
+        &self,
+        events: Vec<Event<'a>>,
+        _context: &mut RenderContext,
+    ) -> Vec<Event<'a>> {
+        let mut output = Vec::with_capacity(events.len());
+        let mut code_block: Option<(String, String)> = None;
+
+        for event in events {
+            match event {
+                Event::Start(Tag::CodeBlock(kind)) => {
+                    let lang = match kind {
+                        CodeBlockKind::Fenced(lang) => lang.to_string(),
+                        CodeBlockKind::Indented => String::new(),
+                    };
+                    code_block = Some((lang, String::new()));
+                }
+                Event::End(TagEnd::CodeBlock) => {
+                    if let Some((lang, code)) = code_block.take() {
+                        output.push(Event::Html(CowStr::from(highlight_code(&code, &lang))));
+                    }
+                }
+                Event::Text(text) if code_block.is_some() => {
+                    if let Some((_, ref mut code)) = code_block {
+                        code.push_str(&text);
+                    }
+                }
+                _ => output.push(event),
+            }
+        }
+
+        output
+    }
+}
+
+/// Gives every heading a collision-free id and permalink, recording TOC entries
+#[derive(Debug, Default, Clone, Copy)]
+pub struct HeadingAnchors;
+
+impl EventTransform for HeadingAnchors {
+    fn transform<'a>(&self, events: Vec<Event<'a>>, context: &mut RenderContext) -> Vec<Event<'a>> {
+        let (events, entries) = anchor_headings(events);
+        context.toc_entries = entries;
+        events
+    }
+}
+
+/// Options controlling the default rendering pipeline
+#[derive(Debug, Clone)]
+pub struct RenderOptions {
+    /// Markdown extensions passed to the parser
+    pub markdown: Options,
+    /// Highlight code blocks with syntect
+    pub highlight_code: bool,
+    /// Add ids and permalinks to headings (required for TOC links to work)
+    pub heading_anchors: bool,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            markdown: Options::all(),
+            highlight_code: true,
+            heading_anchors: true,
+        }
+    }
+}
+
+/// Markdown renderer shared by posts, pages and any offline tooling
+pub struct Renderer {
+    options: RenderOptions,
+    transforms: Vec<Box<dyn EventTransform>>,
+}
+
+impl Default for Renderer {
+    fn default() -> Self {
+        Self::new(RenderOptions::default())
+    }
+}
+
+impl Renderer {
+    /// Create a renderer with the built-in pipeline selected by `options`
+    pub fn new(options: RenderOptions) -> Self {
+        let mut transforms: Vec<Box<dyn EventTransform>> = Vec::new();
+
+        if options.highlight_code {
+            transforms.push(Box::new(SyntaxHighlighting));
+        }
+        if options.heading_anchors {
+            transforms.push(Box::new(HeadingAnchors));
+        }
+
+        Self {
+            options,
+            transforms,
+        }
+    }
+
+    /// Append a custom transform to the end of the pipeline
+    pub fn with_transform(mut self, transform: impl EventTransform + 'static) -> Self {
+        self.transforms.push(Box::new(transform));
+        self
+    }
+
+    /// The options this renderer was built with
+    pub fn options(&self) -> &RenderOptions {
+        &self.options
+    }
+
+    /// Stable hash of the parser options and transform pipeline
+    ///
+    /// Two renderers with the same fingerprint produce identical output for
+    /// the same input, so the fingerprint can be used as part of a cache key.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = ContentHasher::new();
+        hasher.write(&self.options.markdown.bits().to_le_bytes());
+        for transform in &self.transforms {
+            hasher.write_str(&transform.fingerprint());
+        }
+        hasher.finish()
+    }
+
+    /// Render markdown to HTML, optionally including a table of contents
+    pub fn render(&self, markdown: &str, with_toc: bool) -> RenderedContent {
+        let mut context = RenderContext::default();
+        let mut events: Vec<Event> = Parser::new_ext(markdown, self.options.markdown).collect();
+
+        for transform in &self.transforms {
+            events = transform.transform(events, &mut context);
+        }
+
+        let mut html = String::new();
+        pulldown_cmark::html::push_html(&mut html, events.into_iter());
+
+        let toc = if with_toc {
+            Some(render_toc(&context.toc_entries))
+        } else {
+            None
+        };
+
+        RenderedContent { html, toc }
+    }
+
+    /// Render a post, honouring its `toc` frontmatter flag
+    pub fn render_post(&self, post: &Post) -> RenderedContent {
+        self.render(&post.raw_content, post.frontmatter.toc)
+    }
+
+    /// Render a page, honouring its `toc` frontmatter flag
+    pub fn render_page(&self, page: &Page) -> RenderedContent {
+        self.render(&page.raw_content, page.toc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MARKDOWN: &str = r#"## Setup
+
+```rust
+fn main() {}
+```
+
+## Usage
+"#;
+
+    #[test]
+    fn test_default_pipeline() {
+        let rendered = Renderer::default().render(MARKDOWN, true);
+
+        assert!(rendered.html.contains("<h2 id=\"setup\">"));
+        assert!(rendered.html.contains("style="));
+        let toc = rendered.toc.unwrap();
+        assert!(toc.contains("href=\"#setup\""));
+        assert!(toc.contains("href=\"#usage\""));
+    }
+
+    #[test]
+    fn test_pipeline_can_be_disabled() {
+        let renderer = Renderer::new(RenderOptions {
+            highlight_code: false,
+            heading_anchors: false,
+            ..RenderOptions::default()
+        });
+        let rendered = renderer.render(MARKDOWN, false);
+
+        assert!(rendered.html.contains("<h2>Setup</h2>"));
+        assert!(rendered.html.contains("<code class=\"language-rust\">"));
+        assert!(rendered.toc.is_none());
+    }
+
+    #[test]
+    fn test_fingerprint_tracks_pipeline() {
+        let default = Renderer::default();
+        let plain = Renderer::new(RenderOptions {
+            highlight_code: false,
+            ..RenderOptions::default()
+        });
+
+        assert_eq!(default.fingerprint(), Renderer::default().fingerprint());
+        assert_ne!(default.fingerprint(), plain.fingerprint());
+    }
+
+    #[test]
+    fn test_custom_transform() {
+        struct Shout;
+
+        impl EventTransform for Shout {
+            fn transform<'a>(
+                &self,
+                events: Vec<Event<'a>>,
+                _context: &mut RenderContext,
+            ) -> Vec<Event<'a>> {
+                events
+                    .into_iter()
+                    .map(|event| match event {
+                        Event::Text(text) => Event::Text(text.to_uppercase().into()),
+                        other => other,
+                    })
+                    .collect()
+            }
+        }
+
+        let rendered = Renderer::default()
+            .with_transform(Shout)
+            .render("Hello *there*", false);
+        assert!(rendered.html.contains("HELLO <em>THERE</em>"));
+    }
+}
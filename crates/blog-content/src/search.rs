@@ -0,0 +1,488 @@
+//! Full-text search over posts
+
+use std::collections::{BTreeMap, HashMap};
+
+use pulldown_cmark::{Event, Parser, TagEnd};
+use serde::Serialize;
+
+use crate::models::Post;
+use crate::toc::{extract_toc, TocEntry};
+
+/// How much an occurrence in each field counts towards a post's score
+const TITLE_WEIGHT: f32 = 10.0;
+const TAG_WEIGHT: f32 = 6.0;
+const DESCRIPTION_WEIGHT: f32 = 4.0;
+const BODY_WEIGHT: f32 = 1.0;
+const HEADING_WEIGHT: f32 = 5.0;
+const EXCERPT_WEIGHT: f32 = 2.0;
+
+/// Score multiplier for a query term that only matches the start of a word
+const PREFIX_FACTOR: f32 = 0.5;
+
+/// Approximate length of a result snippet, in characters
+const SNIPPET_CHARS: usize = 180;
+
+/// Length of a client index excerpt taken from the body, in words
+const EXCERPT_WORDS: usize = 30;
+
+/// A post matching a search, best matches first
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SearchHit {
+    pub slug: String,
+    pub score: f32,
+    /// Escaped excerpt around the first match, with matches in `<mark>`
+    pub snippet_html: String,
+}
+
+/// Text kept per post for building snippets
+#[derive(Debug, Clone)]
+struct SearchDoc {
+    slug: String,
+    description: String,
+    body: String,
+}
+
+/// Inverted index over post titles, descriptions, tags and body text
+#[derive(Debug, Clone, Default)]
+pub struct SearchIndex {
+    docs: Vec<SearchDoc>,
+    /// Word to `(doc, weight)` postings; ordered so prefixes are a range scan
+    terms: BTreeMap<String, Vec<(usize, f32)>>,
+}
+
+impl SearchIndex {
+    /// Index posts; ties in score are broken by this order, so pass them
+    /// newest first
+    pub fn build<'a>(posts: impl IntoIterator<Item = &'a Post>) -> Self {
+        let mut index = Self::default();
+
+        for post in posts {
+            let doc = index.docs.len();
+            let body = plain_text(&post.raw_content);
+            let description = post.frontmatter.description.clone().unwrap_or_default();
+
+            let mut weights: HashMap<String, f32> = HashMap::new();
+            add_words(&mut weights, post.title(), TITLE_WEIGHT);
+            for tag in &post.frontmatter.tags {
+                add_words(&mut weights, tag, TAG_WEIGHT);
+            }
+            add_words(&mut weights, &description, DESCRIPTION_WEIGHT);
+            add_words(&mut weights, &body, BODY_WEIGHT);
+
+            for (term, weight) in weights {
+                // Dampen repetition so long posts don't drown out titles
+                index
+                    .terms
+                    .entry(term)
+                    .or_default()
+                    .push((doc, weight.ln_1p()));
+            }
+
+            index.docs.push(SearchDoc {
+                slug: post.slug().to_string(),
+                description,
+                body,
+            });
+        }
+
+        index
+    }
+
+    pub fn len(&self) -> usize {
+        self.docs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.docs.is_empty()
+    }
+
+    /// Find posts containing every word of `query`
+    ///
+    /// Each query word also matches longer words it is a prefix of, at a
+    /// lower score, so partially typed queries find results.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<SearchHit> {
+        let mut query_terms: Vec<String> = Vec::new();
+        for (_, word) in words(query) {
+            let word = word.to_lowercase();
+            if !query_terms.contains(&word) {
+                query_terms.push(word);
+            }
+        }
+        if query_terms.is_empty() {
+            return Vec::new();
+        }
+
+        let doc_count = self.docs.len() as f32;
+        let mut scores: HashMap<usize, (f32, usize)> = HashMap::new();
+
+        for term in &query_terms {
+            let mut term_scores: HashMap<usize, f32> = HashMap::new();
+
+            let matches = self
+                .terms
+                .range(term.clone()..)
+                .take_while(|(word, _)| word.starts_with(term.as_str()));
+            for (word, postings) in matches {
+                let factor = if word == term { 1.0 } else { PREFIX_FACTOR };
+                let idf = (1.0 + doc_count / postings.len() as f32).ln();
+
+                for &(doc, weight) in postings {
+                    let score = term_scores.entry(doc).or_default();
+                    *score = score.max(weight * idf * factor);
+                }
+            }
+
+            for (doc, score) in term_scores {
+                let entry = scores.entry(doc).or_default();
+                entry.0 += score;
+                entry.1 += 1;
+            }
+        }
+
+        let mut hits: Vec<_> = scores
+            .into_iter()
+            .filter(|(_, (_, matched))| *matched == query_terms.len())
+            .map(|(doc, (score, _))| (doc, score))
+            .collect();
+        hits.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        hits.into_iter()
+            .take(limit)
+            .map(|(doc, score)| {
+                let doc = &self.docs[doc];
+                SearchHit {
+                    slug: doc.slug.clone(),
+                    score,
+                    snippet_html: snippet(doc, &query_terms),
+                }
+            })
+            .collect()
+    }
+}
+
+/// A post's entry in the client-side index
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ClientDoc {
+    pub slug: String,
+    pub title: String,
+    pub date: String,
+    pub tags: Vec<String>,
+    /// Section headings, so results can link straight to a section
+    pub headings: Vec<ClientHeading>,
+    pub excerpt: String,
+    /// Estimated minutes to read
+    pub reading_time: usize,
+}
+
+/// A heading in a [`ClientDoc`]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ClientHeading {
+    pub id: String,
+    pub text: String,
+}
+
+impl From<TocEntry> for ClientHeading {
+    fn from(entry: TocEntry) -> Self {
+        Self {
+            id: entry.id,
+            text: entry.text,
+        }
+    }
+}
+
+/// Compact index for searching in the browser, serialized as
+/// `/search-index.json`
+///
+/// Covers titles, headings, tags and excerpts rather than full post text to
+/// keep the download small. `terms` maps each word to `[doc, weight]` pairs,
+/// where `doc` indexes `docs` and `weight` is the sum of the field weights of
+/// the word's occurrences; clients should rank like [`SearchIndex`], with
+/// `ln(1 + weight)` scaled by the word's rarity.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ClientIndex {
+    pub version: u32,
+    pub docs: Vec<ClientDoc>,
+    pub terms: BTreeMap<String, Vec<(usize, u32)>>,
+}
+
+impl ClientIndex {
+    pub const VERSION: u32 = 1;
+
+    /// Index posts, keeping their order
+    pub fn build<'a>(posts: impl IntoIterator<Item = &'a Post>) -> Self {
+        let mut index = Self {
+            version: Self::VERSION,
+            ..Self::default()
+        };
+
+        for post in posts {
+            let doc = index.docs.len();
+            let headings: Vec<ClientHeading> = extract_toc(&post.raw_content)
+                .into_iter()
+                .map(ClientHeading::from)
+                .collect();
+            let excerpt = match &post.frontmatter.description {
+                Some(description) => description.clone(),
+                None => excerpt(&plain_text(&post.raw_content), EXCERPT_WORDS),
+            };
+
+            let mut weights: HashMap<String, f32> = HashMap::new();
+            add_words(&mut weights, post.title(), TITLE_WEIGHT);
+            for tag in &post.frontmatter.tags {
+                add_words(&mut weights, tag, TAG_WEIGHT);
+            }
+            for heading in &headings {
+                add_words(&mut weights, &heading.text, HEADING_WEIGHT);
+            }
+            add_words(&mut weights, &excerpt, EXCERPT_WEIGHT);
+
+            for (term, weight) in weights {
+                index
+                    .terms
+                    .entry(term)
+                    .or_default()
+                    .push((doc, weight.round() as u32));
+            }
+
+            index.docs.push(ClientDoc {
+                slug: post.slug().to_string(),
+                title: post.title().to_string(),
+                date: post.date().format("%Y-%m-%d").to_string(),
+                tags: post.frontmatter.tags.clone(),
+                headings,
+                excerpt,
+                reading_time: post.stats.reading_time,
+            });
+        }
+
+        // Postings were pushed per doc, so each list is already in doc order
+        index
+    }
+}
+
+/// Add `weight` to the entry of every word in `text`
+fn add_words(weights: &mut HashMap<String, f32>, text: &str, weight: f32) {
+    for (_, word) in words(text) {
+        *weights.entry(word.to_lowercase()).or_default() += weight;
+    }
+}
+
+/// The first `max_words` words of `text`, with an ellipsis if cut short
+pub fn excerpt(text: &str, max_words: usize) -> String {
+    let mut words = text.split_whitespace();
+    let mut excerpt = words.by_ref().take(max_words).collect::<Vec<_>>().join(" ");
+    if words.next().is_some() {
+        excerpt.push('\u{2026}');
+    }
+    excerpt
+}
+
+/// Readable text of a markdown document, without markup
+pub fn plain_text(markdown: &str) -> String {
+    let mut text = String::with_capacity(markdown.len());
+
+    for event in Parser::new(markdown) {
+        match event {
+            Event::Text(t) | Event::Code(t) => text.push_str(&t),
+            Event::SoftBreak | Event::HardBreak => text.push(' '),
+            Event::End(
+                TagEnd::Paragraph | TagEnd::Heading(_) | TagEnd::Item | TagEnd::CodeBlock,
+            ) => text.push(' '),
+            _ => {}
+        }
+    }
+
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Words in `text` along with their byte offsets
+fn words(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut start = None;
+    let mut chars = text
+        .char_indices()
+        .chain(std::iter::once((text.len(), ' ')));
+
+    std::iter::from_fn(move || {
+        for (i, c) in chars.by_ref() {
+            match (start, c.is_alphanumeric()) {
+                (None, true) => start = Some(i),
+                (Some(s), false) => {
+                    start = None;
+                    return Some((s, &text[s..i]));
+                }
+                _ => {}
+            }
+        }
+        None
+    })
+}
+
+fn matches_query(word: &str, query_terms: &[String]) -> bool {
+    let word = word.to_lowercase();
+    query_terms
+        .iter()
+        .any(|term| word.starts_with(term.as_str()))
+}
+
+/// Excerpt of the body around the first matching word, falling back to the
+/// description and then the start of the body
+fn snippet(doc: &SearchDoc, query_terms: &[String]) -> String {
+    let first_match = |text: &str| {
+        words(text)
+            .find(|(_, word)| matches_query(word, query_terms))
+            .map(|(i, _)| i)
+    };
+
+    let (text, position) = match first_match(&doc.body) {
+        Some(i) => (doc.body.as_str(), i),
+        None if !doc.description.is_empty() => (doc.description.as_str(), 0),
+        None => (doc.body.as_str(), 0),
+    };
+
+    // Start a little before the match, on a word boundary
+    let mut start = text[..position]
+        .char_indices()
+        .rev()
+        .nth(SNIPPET_CHARS / 3)
+        .map(|(i, _)| i)
+        .unwrap_or(0);
+    if start > 0 {
+        start = text[start..]
+            .find(' ')
+            .map(|i| start + i + 1)
+            .filter(|&i| i <= position)
+            .unwrap_or(position);
+    }
+
+    let mut end = text[start..]
+        .char_indices()
+        .nth(SNIPPET_CHARS)
+        .map(|(i, _)| start + i)
+        .unwrap_or(text.len());
+    if end < text.len() {
+        end = text[..end]
+            .rfind(' ')
+            .filter(|&i| i > position)
+            .unwrap_or(end);
+    }
+
+    let excerpt = &text[start..end];
+    let mut html = String::with_capacity(excerpt.len() + 32);
+    if start > 0 {
+        html.push('\u{2026}');
+    }
+
+    let mut last = 0;
+    for (i, word) in words(excerpt) {
+        if matches_query(word, query_terms) {
+            html.push_str(&html_escape::encode_text(&excerpt[last..i]));
+            html.push_str("<mark>");
+            html.push_str(&html_escape::encode_text(word));
+            html.push_str("</mark>");
+            last = i + word.len();
+        }
+    }
+    html.push_str(&html_escape::encode_text(&excerpt[last..]));
+
+    if end < text.len() {
+        html.push('\u{2026}');
+    }
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Frontmatter;
+
+    fn post(slug: &str, title: &str, tags: &str, body: &str) -> Post {
+        let yaml = format!(
+            "title: \"{}\"\nslug: \"{}\"\ndate: 2025-01-01\ntags: {}\n",
+            title, slug, tags
+        );
+        let frontmatter: Frontmatter = serde_yaml::from_str(&yaml).unwrap();
+        Post::new(frontmatter, body.to_string(), format!("{}.md", slug))
+    }
+
+    fn slugs(hits: &[SearchHit]) -> Vec<&str> {
+        hits.iter().map(|h| h.slug.as_str()).collect()
+    }
+
+    #[test]
+    fn test_plain_text() {
+        assert_eq!(
+            plain_text("# Hello\n\nSome *emphasis* and `code`.\n\n```rust\nfn main() {}\n```\n"),
+            "Hello Some emphasis and code. fn main() {}"
+        );
+    }
+
+    #[test]
+    fn test_ranking_and_prefix_matching() {
+        let posts = vec![
+            post("mention", "Notes", "[]", "We briefly tried caching here."),
+            post("titled", "Caching in Rust", "[]", "All about it."),
+            post("tagged", "Performance", "[\"caching\"]", "Fast."),
+        ];
+        let index = SearchIndex::build(&posts);
+
+        assert_eq!(
+            slugs(&index.search("caching", 10)),
+            vec!["titled", "tagged", "mention"]
+        );
+        assert_eq!(slugs(&index.search("cach", 10)).len(), 3);
+        assert_eq!(slugs(&index.search("caching rust", 10)), vec!["titled"]);
+        assert!(index.search("missing", 10).is_empty());
+        assert!(index.search("  ", 10).is_empty());
+        assert_eq!(index.search("caching", 1).len(), 1);
+    }
+
+    #[test]
+    fn test_client_index() {
+        let mut described = post(
+            "described",
+            "Caching Notes",
+            "[\"rust\"]",
+            "## Warm Caches\n\nBody.",
+        );
+        described.frontmatter.description = Some("All about caches.".to_string());
+        let posts = vec![described, post("plain", "Other", "[]", &"word ".repeat(40))];
+
+        let index = ClientIndex::build(&posts);
+        assert_eq!(index.version, ClientIndex::VERSION);
+
+        let doc = &index.docs[0];
+        assert_eq!(doc.date, "2025-01-01");
+        assert_eq!(doc.excerpt, "All about caches.");
+        assert_eq!(
+            doc.headings,
+            vec![ClientHeading {
+                id: "warm-caches".to_string(),
+                text: "Warm Caches".to_string()
+            }]
+        );
+        assert_eq!(index.terms["caches"], vec![(0, 7)]);
+        assert_eq!(index.terms["rust"], vec![(0, 6)]);
+        assert!(!index.terms.contains_key("body"));
+
+        assert_eq!(index.docs[1].excerpt.split(' ').count(), EXCERPT_WORDS);
+        assert!(index.docs[1].excerpt.ends_with('\u{2026}'));
+        assert_eq!(index.terms["word"], vec![(1, 60)]);
+    }
+
+    #[test]
+    fn test_snippet_highlights_matches() {
+        let body = format!(
+            "{} Warm \"caches\" & more. {}",
+            "word ".repeat(80),
+            "tail ".repeat(80)
+        );
+        let posts = vec![post("one", "One", "[]", &body)];
+        let index = SearchIndex::build(&posts);
+
+        let snippet = &index.search("cache", 10)[0].snippet_html;
+        assert!(snippet.starts_with('\u{2026}'));
+        assert!(snippet.ends_with('\u{2026}'));
+        assert!(snippet.contains("Warm \"<mark>caches</mark>\" &amp; more."));
+        assert!(snippet.chars().count() < SNIPPET_CHARS + 40);
+    }
+}
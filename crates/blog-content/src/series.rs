@@ -0,0 +1,261 @@
+//! Ordered series of posts
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::models::{Post, PostLink};
+
+/// A post's place in a series, from the `series` frontmatter field
+///
+/// Both `series: "Name"` and `series: { name: "Name", part: 2 }` are accepted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SeriesRef {
+    pub name: String,
+    /// Explicit position in the series; posts without one follow the
+    /// numbered parts in date order
+    pub part: Option<u32>,
+}
+
+impl<'de> Deserialize<'de> for SeriesRef {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Name(String),
+            Full {
+                name: String,
+                #[serde(default)]
+                part: Option<u32>,
+            },
+        }
+
+        let (name, part) = match Raw::deserialize(deserializer)? {
+            Raw::Name(name) => (name, None),
+            Raw::Full { name, part } => (name, part),
+        };
+
+        let name = name.trim().to_string();
+        if slugify(&name).is_empty() {
+            return Err(serde::de::Error::custom("series name must not be empty"));
+        }
+
+        Ok(Self { name, part })
+    }
+}
+
+impl SeriesRef {
+    /// URL segment of the series, e.g. `getting-started-with-rust`
+    pub fn slug(&self) -> String {
+        slugify(&self.name)
+    }
+}
+
+/// Turn a series name into a URL segment
+///
+/// Letters and digits are lowercased and every other run of characters
+/// becomes a single `-`, so `"Rust: The Basics"` becomes `"rust-the-basics"`.
+pub fn slugify(name: &str) -> String {
+    name.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// A named, ordered group of posts
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Series {
+    pub slug: String,
+    pub name: String,
+    /// Posts in reading order
+    pub parts: Vec<PostLink>,
+}
+
+impl Series {
+    /// 1-based position of a post in the series
+    pub fn position(&self, post_slug: &str) -> Option<usize> {
+        self.parts
+            .iter()
+            .position(|p| p.slug == post_slug)
+            .map(|i| i + 1)
+    }
+}
+
+/// Series navigation shown on a post, e.g. "Part 2 of 5"
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SeriesNav {
+    pub slug: String,
+    pub name: String,
+    pub position: usize,
+    pub total: usize,
+    pub prev: Option<PostLink>,
+    pub next: Option<PostLink>,
+}
+
+/// Every series used by a set of posts
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct SeriesIndex {
+    series: Vec<Series>,
+}
+
+impl SeriesIndex {
+    /// Group posts by series
+    ///
+    /// Parts with a `part` number come first, in that order; the rest follow
+    /// by date. Series are ordered by name.
+    pub fn build<'a>(posts: impl IntoIterator<Item = &'a Post>) -> Self {
+        let mut grouped: Vec<(Series, Vec<Option<u32>>)> = Vec::new();
+
+        for post in posts {
+            let Some(series_ref) = post.frontmatter.series.as_ref() else {
+                continue;
+            };
+            let slug = series_ref.slug();
+
+            let index = match grouped.iter().position(|(s, _)| s.slug == slug) {
+                Some(index) => index,
+                None => {
+                    grouped.push((
+                        Series {
+                            slug,
+                            name: series_ref.name.clone(),
+                            parts: Vec::new(),
+                        },
+                        Vec::new(),
+                    ));
+                    grouped.len() - 1
+                }
+            };
+
+            let (series, numbers) = &mut grouped[index];
+            series.parts.push(PostLink::from(post));
+            numbers.push(series_ref.part);
+        }
+
+        let mut series: Vec<Series> = grouped
+            .into_iter()
+            .map(|(mut series, numbers)| {
+                let mut seen = HashSet::new();
+                for part in numbers.iter().flatten() {
+                    if !seen.insert(part) {
+                        tracing::warn!("Series {:?} has more than one part {}", series.name, part);
+                    }
+                }
+
+                let mut parts: Vec<_> = numbers.into_iter().zip(series.parts).collect();
+                parts.sort_by_key(|(number, part)| (number.unwrap_or(u32::MAX), part.date));
+                series.parts = parts.into_iter().map(|(_, part)| part).collect();
+                series
+            })
+            .collect();
+        series.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Self { series }
+    }
+
+    /// Look up a series by slug
+    pub fn get(&self, slug: &str) -> Option<&Series> {
+        self.series.iter().find(|s| s.slug == slug)
+    }
+
+    /// Navigation for a post that belongs to a series
+    pub fn nav_for(&self, post: &Post) -> Option<SeriesNav> {
+        let series = self.get(&post.frontmatter.series.as_ref()?.slug())?;
+        let position = series.position(post.slug())?;
+
+        Some(SeriesNav {
+            slug: series.slug.clone(),
+            name: series.name.clone(),
+            position,
+            total: series.parts.len(),
+            prev: position.checked_sub(2).map(|i| series.parts[i].clone()),
+            next: series.parts.get(position).cloned(),
+        })
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Series> {
+        self.series.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.series.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Frontmatter;
+
+    fn post_in_series(slug: &str, date: &str, series: &str) -> Post {
+        let yaml = format!(
+            "title: \"{}\"\nslug: \"{}\"\ndate: {}\nseries: {}\n",
+            slug, slug, date, series
+        );
+        let frontmatter: Frontmatter = serde_yaml::from_str(&yaml).unwrap();
+        Post::new(frontmatter, String::new(), format!("{}.md", slug))
+    }
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("Rust: The Basics"), "rust-the-basics");
+        assert_eq!(slugify("  Nix  for Teams!"), "nix-for-teams");
+    }
+
+    #[test]
+    fn test_series_frontmatter_forms() {
+        let post = post_in_series("a", "2025-01-01", "\"Learning Rust\"");
+        assert_eq!(
+            post.frontmatter.series,
+            Some(SeriesRef {
+                name: "Learning Rust".to_string(),
+                part: None
+            })
+        );
+
+        let post = post_in_series("b", "2025-01-01", "{ name: \"Learning Rust\", part: 2 }");
+        assert_eq!(post.frontmatter.series.unwrap().part, Some(2));
+
+        let yaml = "title: \"c\"\nslug: \"c\"\ndate: 2025-01-01\nseries: \" \"\n";
+        assert!(serde_yaml::from_str::<Frontmatter>(yaml).is_err());
+    }
+
+    #[test]
+    fn test_series_order_and_nav() {
+        let posts = vec![
+            post_in_series("extra", "2025-01-01", "\"Learning Rust\""),
+            post_in_series(
+                "second",
+                "2025-01-05",
+                "{ name: \"Learning Rust\", part: 2 }",
+            ),
+            post_in_series(
+                "first",
+                "2025-01-09",
+                "{ name: \"learning rust\", part: 1 }",
+            ),
+            post_in_series("other", "2025-01-02", "\"Nix\""),
+        ];
+
+        let index = SeriesIndex::build(&posts);
+        let names: Vec<_> = index.iter().map(|s| s.slug.as_str()).collect();
+        assert_eq!(names, vec!["learning-rust", "nix"]);
+
+        let series = index.get("learning-rust").unwrap();
+        let order: Vec<_> = series.parts.iter().map(|p| p.slug.as_str()).collect();
+        assert_eq!(order, vec!["first", "second", "extra"]);
+
+        let nav = index.nav_for(&posts[1]).unwrap();
+        assert_eq!((nav.position, nav.total), (2, 3));
+        assert_eq!(nav.prev.unwrap().slug, "first");
+        assert_eq!(nav.next.unwrap().slug, "extra");
+
+        let nav = index.nav_for(&posts[2]).unwrap();
+        assert!(nav.prev.is_none());
+        assert!(index.nav_for(&posts[3]).unwrap().next.is_none());
+    }
+}
@@ -0,0 +1,82 @@
+//! Length measures of a post, computed when it is loaded
+
+use pulldown_cmark::{Event, Parser, Tag, TagEnd};
+use serde::{Deserialize, Serialize};
+
+/// Reading speed used to estimate reading time
+pub const WORDS_PER_MINUTE: usize = 200;
+
+/// Word count, reading time and code block count of a post's body
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReadingStats {
+    /// Words of prose, not counting code blocks
+    pub word_count: usize,
+    /// Estimated minutes to read, rounded up and at least one
+    pub reading_time: usize,
+    /// Fenced and indented code blocks
+    pub code_blocks: usize,
+}
+
+impl ReadingStats {
+    /// Measure markdown without its frontmatter
+    ///
+    /// Only text events outside code blocks are counted, so markup, HTML and
+    /// code listings are left out; inline code is read as part of its
+    /// sentence and counts.
+    pub fn from_markdown(markdown: &str) -> Self {
+        let mut text = String::with_capacity(markdown.len());
+        let mut code_blocks = 0;
+        let mut in_code_block = false;
+
+        for event in Parser::new(markdown) {
+            match event {
+                Event::Start(Tag::CodeBlock(_)) => {
+                    code_blocks += 1;
+                    in_code_block = true;
+                }
+                Event::End(TagEnd::CodeBlock) => in_code_block = false,
+                Event::Text(t) | Event::Code(t) if !in_code_block => text.push_str(&t),
+                Event::SoftBreak | Event::HardBreak => text.push(' '),
+                Event::End(
+                    TagEnd::Paragraph | TagEnd::Heading(_) | TagEnd::Item | TagEnd::TableCell,
+                ) => text.push(' '),
+                _ => {}
+            }
+        }
+
+        // Stray punctuation such as a spaced dash is not a word
+        let word_count = text
+            .split_whitespace()
+            .filter(|word| word.chars().any(char::is_alphanumeric))
+            .count();
+
+        Self {
+            word_count,
+            reading_time: word_count.div_ceil(WORDS_PER_MINUTE).max(1),
+            code_blocks,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts_prose_only() {
+        let markdown = "# Getting *started*\n\nSome `inline` text \u{2014} with a [link](https://example.com).\n\n```rust\nfn main() { println!(\"not counted\"); }\n```\n\n    indented code\n\n- one\n- two\n\n<div>html</div>\n";
+        let stats = ReadingStats::from_markdown(markdown);
+        assert_eq!(stats.word_count, 10);
+        assert_eq!(stats.code_blocks, 2);
+        assert_eq!(stats.reading_time, 1);
+    }
+
+    #[test]
+    fn test_reading_time_rounds_up() {
+        let words = |n: usize| vec!["word"; n].join(" ");
+        assert_eq!(ReadingStats::from_markdown("").reading_time, 1);
+        assert_eq!(ReadingStats::from_markdown(&words(200)).reading_time, 1);
+        assert_eq!(ReadingStats::from_markdown(&words(201)).reading_time, 2);
+        assert_eq!(ReadingStats::from_markdown(&words(1000)).word_count, 1000);
+    }
+}
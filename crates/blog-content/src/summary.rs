@@ -0,0 +1,218 @@
+//! Post summaries for listings and feeds
+//!
+//! A summary is everything before a `<!-- more -->` marker, or failing that
+//! the opening words of the post. Either way it is rendered from markdown
+//! events rather than cut out of rendered HTML, so every tag it opens is
+//! closed, and raw HTML is left out.
+
+use pulldown_cmark::{html, CowStr, Event, Options, Parser, Tag, TagEnd};
+
+/// Marker separating a post's summary from the rest of it
+pub const MORE_MARKER: &str = "<!-- more -->";
+
+/// Length of a summary taken from the start of a post without a marker
+pub const SUMMARY_WORDS: usize = 50;
+
+/// Summary of a post's markdown body as HTML
+///
+/// With a [`MORE_MARKER`], the summary is the markdown before it, as
+/// written. Otherwise it is the first `max_words` words of the post's prose,
+/// ending with an ellipsis if the post goes on; headings, code blocks,
+/// images and tables are skipped so the excerpt reads as running text.
+pub fn summary_html(markdown: &str, max_words: usize) -> String {
+    let parser = || Parser::new_ext(markdown, Options::all());
+    let has_marker = parser().any(|event| is_more_marker(&event));
+    let limit = (!has_marker).then_some(max_words);
+
+    let mut events = parser();
+    let mut summary: Vec<Event> = Vec::new();
+    let mut open: Vec<TagEnd> = Vec::new();
+    let mut skipping = 0;
+    let mut words = 0;
+    // Whether the last text ended mid-word, as text events can split words
+    let mut in_word = false;
+    // Set when the word limit cut the post short, to whether prose was left out
+    let mut cut: Option<bool> = None;
+
+    for event in events.by_ref() {
+        if is_more_marker(&event) {
+            break;
+        }
+
+        // Inside an element left out of the summary; track nesting only
+        if skipping > 0 {
+            match event {
+                Event::Start(_) => skipping += 1,
+                Event::End(_) => skipping -= 1,
+                _ => {}
+            }
+            continue;
+        }
+
+        let full = limit.is_some_and(|limit| words >= limit && !in_word);
+        match event {
+            Event::Start(tag) if is_skipped(&tag, limit.is_some()) => {
+                skipping = 1;
+                in_word = false;
+            }
+            Event::Start(_) if full => {
+                cut = Some(false);
+                break;
+            }
+            Event::Start(tag) => {
+                open.push(tag.to_end());
+                summary.push(Event::Start(tag));
+            }
+            Event::End(end) => {
+                in_word &= is_inline(&end);
+                open.pop();
+                summary.push(Event::End(end));
+            }
+            Event::Text(text) => {
+                let end = limit.and_then(|limit| word_cut(&text, limit, &mut words, &mut in_word));
+                match end {
+                    Some(end) => {
+                        summary.push(Event::Text(CowStr::from(text[..end].to_string())));
+                        cut = Some(true);
+                        break;
+                    }
+                    None => summary.push(Event::Text(text)),
+                }
+            }
+            Event::Code(_) if full => {
+                cut = Some(true);
+                break;
+            }
+            Event::Code(code) => {
+                words += code.split_whitespace().count();
+                in_word = false;
+                summary.push(Event::Code(code));
+            }
+            Event::SoftBreak | Event::HardBreak => {
+                in_word = false;
+                summary.push(event);
+            }
+            Event::Html(_) | Event::InlineHtml(_) | Event::FootnoteReference(_) => {}
+            other => summary.push(other),
+        }
+    }
+
+    // Mark the cut if any prose was left out, and close whatever was open
+    let left_out = cut.is_some_and(|cut| {
+        cut || events.any(|event| match event {
+            Event::Text(text) | Event::Code(text) => !text.trim().is_empty(),
+            _ => false,
+        })
+    });
+    if left_out {
+        let last_text = summary.iter_mut().rev().find_map(|event| match event {
+            Event::Text(text) => Some(text),
+            _ => None,
+        });
+        if let Some(text) = last_text {
+            let kept = text.trim_end().trim_end_matches(['.', ',', ';', ':']);
+            *text = CowStr::from(format!("{}…", kept));
+        }
+    }
+    summary.extend(open.into_iter().rev().map(Event::End));
+
+    let mut html = String::new();
+    html::push_html(&mut html, summary.into_iter());
+    html
+}
+
+fn is_more_marker(event: &Event) -> bool {
+    match event {
+        Event::Html(html) | Event::InlineHtml(html) => html.trim() == MORE_MARKER,
+        _ => false,
+    }
+}
+
+/// Elements never shown in a summary, plus those that only make sense in
+/// the full post when the summary is cut by length
+fn is_skipped(tag: &Tag, by_length: bool) -> bool {
+    match tag {
+        Tag::HtmlBlock | Tag::FootnoteDefinition(_) | Tag::MetadataBlock(_) => true,
+        Tag::CodeBlock(_) | Tag::Image { .. } | Tag::Heading { .. } | Tag::Table(_) => by_length,
+        _ => false,
+    }
+}
+
+/// Whether a word can carry on past the end of this element
+fn is_inline(end: &TagEnd) -> bool {
+    matches!(
+        end,
+        TagEnd::Emphasis | TagEnd::Strong | TagEnd::Strikethrough | TagEnd::Link
+    )
+}
+
+/// Count the words of `text` towards `limit`, returning the byte offset of
+/// the first word past it, if there is one
+fn word_cut(text: &str, limit: usize, words: &mut usize, in_word: &mut bool) -> Option<usize> {
+    for (i, c) in text.char_indices() {
+        if c.is_whitespace() {
+            *in_word = false;
+        } else if !*in_word {
+            if *words >= limit {
+                return Some(i);
+            }
+            *in_word = true;
+            *words += 1;
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_marker_summary() {
+        let markdown = "Intro with **bold** text.\n\n<!-- more -->\n\nThe rest.";
+        assert_eq!(
+            summary_html(markdown, 2),
+            "<p>Intro with <strong>bold</strong> text.</p>\n"
+        );
+
+        // Inline markers close the paragraph they interrupt
+        assert_eq!(
+            summary_html("Short *and <!-- more --> cut*", 50),
+            "<p>Short <em>and </em></p>\n"
+        );
+
+        // A marker in code is code, so the summary is cut by length instead
+        let code = "```html\n<!-- more -->\n```\n\nText";
+        assert_eq!(summary_html(code, 50), "<p>Text</p>\n");
+    }
+
+    #[test]
+    fn test_word_limit_balances_tags() {
+        let markdown = "# Title\n\nOne two *three four five* six.\n\n```\ncode\n```\n\nSeven";
+        assert_eq!(
+            summary_html(markdown, 4),
+            "<p>One two <em>three four…</em></p>\n"
+        );
+        assert_eq!(
+            summary_html(markdown, 6),
+            "<p>One two <em>three four five</em> six…</p>\n"
+        );
+        assert_eq!(
+            summary_html(markdown, 7),
+            "<p>One two <em>three four five</em> six.</p>\n<p>Seven</p>\n"
+        );
+
+        let list = "- first item\n- second item\n\n> quoted words here";
+        assert_eq!(
+            summary_html(list, 3),
+            "<ul>\n<li>first item</li>\n<li>second…</li>\n</ul>\n"
+        );
+    }
+
+    #[test]
+    fn test_raw_html_is_dropped() {
+        let markdown = "<div class=\"note\">\n\nHello <span>there</span>\n\n</div>";
+        assert_eq!(summary_html(markdown, 50), "<p>Hello there</p>\n");
+    }
+}
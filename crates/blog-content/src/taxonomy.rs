@@ -0,0 +1,251 @@
+//! Tags, categories and other ways of grouping posts
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::Post;
+use crate::parser::{load_data_file, ContentError};
+
+/// Normalize a tag so that case, spacing and punctuation variants compare
+/// equal
+///
+/// Letters and digits are lowercased and every other run of characters
+/// becomes a single `-`, so `"Web  Development"` becomes `"web-development"`
+/// and `"CI/CD"` becomes `"ci-cd"`. `+` and `#` are spelled out, so `"C++"`
+/// and `"C#"` become `"c-plus-plus"` and `"c-sharp"` rather than both `"c"`.
+/// The result doubles as the tag's URL segment and export directory name.
+pub fn normalize_tag(tag: &str) -> String {
+    let mut words: Vec<String> = Vec::new();
+    let mut word = String::new();
+
+    for c in tag.chars() {
+        if c.is_alphanumeric() {
+            word.extend(c.to_lowercase());
+            continue;
+        }
+        if !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+        match c {
+            '+' => words.push("plus".to_string()),
+            '#' => words.push("sharp".to_string()),
+            _ => {}
+        }
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+
+    words.join("-")
+}
+
+/// A tag together with the number of posts that use it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TagCount {
+    pub tag: String,
+    pub count: usize,
+}
+
+/// Every tag used by `posts`, most used first and alphabetical within a count
+pub fn tag_counts<'a>(posts: impl IntoIterator<Item = &'a Post>) -> Vec<TagCount> {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for post in posts {
+        for tag in &post.frontmatter.tags {
+            *counts.entry(tag.as_str()).or_default() += 1;
+        }
+    }
+
+    let mut tags: Vec<_> = counts
+        .into_iter()
+        .map(|(tag, count)| TagCount {
+            tag: tag.to_string(),
+            count,
+        })
+        .collect();
+    tags.sort_by_key(|t| std::cmp::Reverse(t.count));
+    tags
+}
+
+/// A post category, as defined in the content's `categories.yaml`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Category {
+    pub slug: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// CSS color used to accent the category's badge
+    #[serde(default)]
+    pub color: Option<String>,
+    /// Short text or emoji shown before the category's name
+    #[serde(default)]
+    pub icon: Option<String>,
+}
+
+/// The categories posts may be filed under, in display order
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Categories {
+    categories: Vec<Category>,
+}
+
+impl Categories {
+    /// Name of the category definitions file in the content directory
+    pub const FILE_NAME: &'static str = "categories.yaml";
+
+    pub fn new(categories: Vec<Category>) -> Self {
+        Self { categories }
+    }
+
+    /// Load category definitions from `content_dir/categories.yaml`
+    ///
+    /// A missing file yields an empty set, so every category used by a post
+    /// will be reported as unknown.
+    pub fn load(content_dir: &Path) -> Result<Self, ContentError> {
+        let categories: Self = load_data_file(content_dir, Self::FILE_NAME)?;
+
+        for (i, category) in categories.categories.iter().enumerate() {
+            if categories.categories[..i]
+                .iter()
+                .any(|c| c.slug == category.slug)
+            {
+                tracing::warn!("Category {:?} is defined more than once", category.slug);
+            }
+        }
+
+        Ok(categories)
+    }
+
+    /// Look up a category by slug
+    pub fn get(&self, slug: &str) -> Option<&Category> {
+        self.categories.iter().find(|c| c.slug == slug)
+    }
+
+    /// Display name for a category slug, falling back to the slug itself
+    pub fn display_name<'a>(&'a self, slug: &'a str) -> &'a str {
+        self.get(slug).map(|c| c.name.as_str()).unwrap_or(slug)
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Category> {
+        self.categories.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.categories.is_empty()
+    }
+
+    /// Posts whose category is not defined, paired with that category
+    pub fn unknown_in<'a>(&self, posts: &'a [Post]) -> Vec<(&'a Post, &'a str)> {
+        posts
+            .iter()
+            .filter_map(|post| {
+                post.frontmatter
+                    .category
+                    .as_deref()
+                    .filter(|slug| self.get(slug).is_none())
+                    .map(|slug| (post, slug))
+            })
+            .collect()
+    }
+
+    /// Log a warning for every post that uses an undefined category
+    pub fn warn_unknown(&self, posts: &[Post]) {
+        for (post, slug) in self.unknown_in(posts) {
+            tracing::warn!(
+                "Post {:?} uses unknown category {:?}; define it in {}",
+                post.slug(),
+                slug,
+                Self::FILE_NAME
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Frontmatter;
+
+    fn post_with_tags(slug: &str, tags: &str) -> Post {
+        let yaml = format!(
+            "title: \"{}\"\nslug: \"{}\"\ndate: 2025-01-01\ntags: {}\n",
+            slug, slug, tags
+        );
+        let frontmatter: Frontmatter = serde_yaml::from_str(&yaml).unwrap();
+        Post::new(frontmatter, String::new(), format!("{}.md", slug))
+    }
+
+    #[test]
+    fn test_normalize_tag() {
+        assert_eq!(normalize_tag("Rust"), "rust");
+        assert_eq!(normalize_tag("  Web \t Development "), "web-development");
+        assert_eq!(normalize_tag("ai-collaboration"), "ai-collaboration");
+        assert_eq!(normalize_tag("CI/CD"), "ci-cd");
+        assert_eq!(normalize_tag("C++"), "c-plus-plus");
+        assert_eq!(normalize_tag("c#"), "c-sharp");
+        assert_eq!(normalize_tag("node.js "), "node-js");
+        assert_eq!(normalize_tag("?!"), "");
+    }
+
+    #[test]
+    fn test_frontmatter_tags_are_normalized() {
+        let post = post_with_tags("one", "[\"Rust\", \"rust \", \"Web Dev\"]");
+        assert_eq!(post.frontmatter.tags, vec!["rust", "web-dev"]);
+    }
+
+    #[test]
+    fn test_load_categories() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join(Categories::FILE_NAME),
+            "- slug: devops\n  name: DevOps\n  description: Shipping things\n  color: \"#22c55e\"\n- slug: reflections\n  name: Reflections\n",
+        )
+        .unwrap();
+
+        let categories = Categories::load(dir.path()).unwrap();
+        let devops = categories.get("devops").unwrap();
+        assert_eq!(devops.description.as_deref(), Some("Shipping things"));
+        assert_eq!(devops.color.as_deref(), Some("#22c55e"));
+        assert_eq!(categories.display_name("reflections"), "Reflections");
+        assert_eq!(categories.display_name("missing"), "missing");
+
+        let posts = vec![
+            post_with_tags("filed", "[]\ncategory: devops"),
+            post_with_tags("stray", "[]\ncategory: gardening"),
+            post_with_tags("none", "[]"),
+        ];
+        let unknown: Vec<_> = categories
+            .unknown_in(&posts)
+            .into_iter()
+            .map(|(post, slug)| (post.slug(), slug))
+            .collect();
+        assert_eq!(unknown, vec![("stray", "gardening")]);
+    }
+
+    #[test]
+    fn test_invalid_categories_file() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join(Categories::FILE_NAME), "- slug: devops\n").unwrap();
+
+        assert!(matches!(
+            Categories::load(dir.path()),
+            Err(ContentError::DataParse(_, _))
+        ));
+        assert!(Categories::load(&dir.path().join("missing"))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_tag_counts() {
+        let posts = vec![
+            post_with_tags("one", "[\"Rust\", \"nix\"]"),
+            post_with_tags("two", "[\"rust\", \"axum\"]"),
+        ];
+
+        let counts = tag_counts(&posts);
+        let tags: Vec<_> = counts.iter().map(|t| (t.tag.as_str(), t.count)).collect();
+        assert_eq!(tags, vec![("rust", 2), ("axum", 1), ("nix", 1)]);
+    }
+}
@@ -0,0 +1,129 @@
+//! Chronological previous/next navigation between posts
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::models::{Post, PostLink};
+
+/// The posts that previous/next navigation moves through
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TimelineScope {
+    All,
+    /// Posts with this author id among their bylines
+    Author(String),
+    /// Posts filed under this category slug
+    Category(String),
+}
+
+/// The posts either side of a post, by date
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Adjacent {
+    /// The next older post
+    pub prev: Option<PostLink>,
+    /// The next newer post
+    pub next: Option<PostLink>,
+}
+
+/// Previous/next posts for every post, within every scope it belongs to
+#[derive(Debug, Clone, Default)]
+pub struct Timeline {
+    scopes: HashMap<TimelineScope, HashMap<String, Adjacent>>,
+}
+
+impl Timeline {
+    /// Compute neighbours from posts sorted newest first, as returned by
+    /// [`crate::load_all_posts`]
+    pub fn build(posts: &[Post]) -> Self {
+        let mut sequences: HashMap<TimelineScope, Vec<&Post>> = HashMap::new();
+
+        for post in posts {
+            sequences.entry(TimelineScope::All).or_default().push(post);
+            for author in &post.authors {
+                sequences
+                    .entry(TimelineScope::Author(author.id.clone()))
+                    .or_default()
+                    .push(post);
+            }
+            if let Some(category) = &post.frontmatter.category {
+                sequences
+                    .entry(TimelineScope::Category(category.clone()))
+                    .or_default()
+                    .push(post);
+            }
+        }
+
+        let scopes = sequences
+            .into_iter()
+            .map(|(scope, sequence)| {
+                let neighbours = sequence
+                    .iter()
+                    .enumerate()
+                    .map(|(i, post)| {
+                        let adjacent = Adjacent {
+                            prev: sequence.get(i + 1).map(|p| PostLink::from(*p)),
+                            next: i.checked_sub(1).map(|j| PostLink::from(sequence[j])),
+                        };
+                        (post.slug().to_string(), adjacent)
+                    })
+                    .collect();
+                (scope, neighbours)
+            })
+            .collect();
+
+        Self { scopes }
+    }
+
+    /// Neighbours of a post within a scope, or `None` if the post is not in it
+    pub fn adjacent(&self, scope: &TimelineScope, slug: &str) -> Option<&Adjacent> {
+        self.scopes.get(scope)?.get(slug)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Frontmatter;
+
+    fn post(slug: &str, date: &str, author: &str, category: &str) -> Post {
+        let yaml = format!(
+            "title: \"{}\"\nslug: \"{}\"\ndate: {}\nauthor: \"{}\"\ncategory: \"{}\"\n",
+            slug, slug, date, author, category
+        );
+        let frontmatter: Frontmatter = serde_yaml::from_str(&yaml).unwrap();
+        Post::new(frontmatter, String::new(), format!("{}.md", slug))
+    }
+
+    fn slugs(adjacent: &Adjacent) -> (Option<&str>, Option<&str>) {
+        (
+            adjacent.prev.as_ref().map(|p| p.slug.as_str()),
+            adjacent.next.as_ref().map(|p| p.slug.as_str()),
+        )
+    }
+
+    #[test]
+    fn test_adjacent_posts_by_scope() {
+        let posts = vec![
+            post("newest", "2025-01-04", "claude", "devops"),
+            post("third", "2025-01-03", "gudnuf", "reflections"),
+            post("second", "2025-01-02", "claude", "reflections"),
+            post("oldest", "2025-01-01", "gudnuf", "devops"),
+        ];
+        let timeline = Timeline::build(&posts);
+
+        let all = timeline.adjacent(&TimelineScope::All, "third").unwrap();
+        assert_eq!(slugs(all), (Some("second"), Some("newest")));
+
+        let newest = timeline.adjacent(&TimelineScope::All, "newest").unwrap();
+        assert_eq!(slugs(newest), (Some("third"), None));
+
+        let by_claude = TimelineScope::Author("claude".to_string());
+        let adjacent = timeline.adjacent(&by_claude, "newest").unwrap();
+        assert_eq!(slugs(adjacent), (Some("second"), None));
+        assert!(timeline.adjacent(&by_claude, "third").is_none());
+
+        let devops = TimelineScope::Category("devops".to_string());
+        let adjacent = timeline.adjacent(&devops, "oldest").unwrap();
+        assert_eq!(slugs(adjacent), (None, Some("newest")));
+    }
+}
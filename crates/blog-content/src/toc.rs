@@ -1,6 +1,8 @@
-//! Table of contents generation
+//! Table of contents generation and heading anchors
 
-use pulldown_cmark::{Event, HeadingLevel, Parser, Tag, TagEnd};
+use std::collections::HashSet;
+
+use pulldown_cmark::{CowStr, Event, HeadingLevel, Options, Parser, Tag, TagEnd};
 
 /// A table of contents entry
 #[derive(Debug, Clone)]
@@ -11,45 +13,109 @@ pub struct TocEntry {
 }
 
 /// Extract table of contents from markdown content
+///
+/// Ids match the anchors emitted by [`anchor_headings`], so TOC links always
+/// point at a heading in the rendered document.
 pub fn extract_toc(markdown: &str) -> Vec<TocEntry> {
-    let parser = Parser::new(markdown);
+    let parser = Parser::new_ext(markdown, Options::all());
+    let (_, entries) = anchor_headings(parser);
+    entries
+}
+
+/// Assign collision-free ids to every heading and add permalink anchors
+///
+/// Explicit `{#id}` attributes are kept as written; other headings get a slug
+/// of their text, suffixed with `-1`, `-2`, ... when the slug is already taken.
+/// Returns the rewritten events along with the TOC entries for the headings.
+pub fn anchor_headings<'a>(
+    events: impl IntoIterator<Item = Event<'a>>,
+) -> (Vec<Event<'a>>, Vec<TocEntry>) {
+    let events: Vec<Event<'a>> = events.into_iter().collect();
+
+    // Reserve explicit ids up front so generated ids never shadow them
+    let mut used: HashSet<String> = events
+        .iter()
+        .filter_map(|event| match event {
+            Event::Start(Tag::Heading { id: Some(id), .. }) => Some(id.to_string()),
+            _ => None,
+        })
+        .collect();
+
+    let mut output = Vec::with_capacity(events.len());
     let mut entries = Vec::new();
-    let mut current_heading: Option<(u8, String)> = None;
+    // Index of the open heading's start event in `output`, plus its text
+    let mut current_heading: Option<(usize, String)> = None;
 
-    for event in parser {
+    for event in events {
         match event {
-            Event::Start(Tag::Heading { level, .. }) => {
-                let level_num = match level {
-                    HeadingLevel::H1 => 1,
-                    HeadingLevel::H2 => 2,
-                    HeadingLevel::H3 => 3,
-                    HeadingLevel::H4 => 4,
-                    HeadingLevel::H5 => 5,
-                    HeadingLevel::H6 => 6,
-                };
-                current_heading = Some((level_num, String::new()));
-            }
-            Event::Text(text) => {
-                if let Some((_, ref mut heading_text)) = current_heading {
-                    heading_text.push_str(&text);
-                }
+            Event::Start(Tag::Heading { .. }) => {
+                current_heading = Some((output.len(), String::new()));
+                output.push(event);
             }
-            Event::Code(code) => {
+            Event::Text(ref text) | Event::Code(ref text) => {
                 if let Some((_, ref mut heading_text)) = current_heading {
-                    heading_text.push_str(&code);
+                    heading_text.push_str(text);
                 }
+                output.push(event);
             }
             Event::End(TagEnd::Heading(_)) => {
-                if let Some((level, text)) = current_heading.take() {
-                    let id = slugify(&text);
-                    entries.push(TocEntry { level, text, id });
+                if let Some((start, text)) = current_heading.take() {
+                    let id = match &mut output[start] {
+                        Event::Start(Tag::Heading { level, id, .. }) => {
+                            let heading_id = match id {
+                                Some(explicit) => explicit.to_string(),
+                                None => unique_id(&slugify(&text), &mut used),
+                            };
+                            *id = Some(CowStr::from(heading_id.clone()));
+                            entries.push(TocEntry {
+                                level: heading_level(*level),
+                                text,
+                                id: heading_id.clone(),
+                            });
+                            heading_id
+                        }
+                        _ => unreachable!("heading start index always points at a heading"),
+                    };
+
+                    output.push(Event::Html(CowStr::from(format!(
+                        "<a class=\"heading-anchor\" href=\"#{}\" aria-label=\"Link to this section\">#</a>",
+                        html_escape::encode_double_quoted_attribute(&id)
+                    ))));
                 }
+                output.push(event);
             }
-            _ => {}
+            _ => output.push(event),
         }
     }
 
-    entries
+    (output, entries)
+}
+
+/// Numeric level for a heading
+fn heading_level(level: HeadingLevel) -> u8 {
+    match level {
+        HeadingLevel::H1 => 1,
+        HeadingLevel::H2 => 2,
+        HeadingLevel::H3 => 3,
+        HeadingLevel::H4 => 4,
+        HeadingLevel::H5 => 5,
+        HeadingLevel::H6 => 6,
+    }
+}
+
+/// Pick `base`, or the first free `base-N`, and mark it as used
+fn unique_id(base: &str, used: &mut HashSet<String>) -> String {
+    let base = if base.is_empty() { "section" } else { base };
+
+    let mut candidate = base.to_string();
+    let mut suffix = 1;
+    while used.contains(&candidate) {
+        candidate = format!("{}-{}", base, suffix);
+        suffix += 1;
+    }
+
+    used.insert(candidate.clone());
+    candidate
 }
 
 /// Generate HTML for table of contents
@@ -70,7 +136,7 @@ pub fn render_toc(entries: &[TocEntry]) -> String {
                 "{}<li class=\"toc-item toc-level-{}\"><a href=\"#{}\">{}</a></li>\n",
                 indent,
                 entry.level,
-                entry.id,
+                html_escape::encode_double_quoted_attribute(&entry.id),
                 html_escape::encode_text(&entry.text)
             ));
         }
@@ -89,8 +155,6 @@ fn slugify(text: &str) -> String {
         .map(|c| {
             if c.is_alphanumeric() {
                 c
-            } else if c.is_whitespace() || c == '-' || c == '_' {
-                '-'
             } else {
                 '-'
             }
@@ -136,6 +200,41 @@ More text.
         assert_eq!(slugify("API v2.0"), "api-v2-0");
     }
 
+    #[test]
+    fn test_duplicate_headings_get_unique_ids() {
+        let markdown = "## Setup\n## Setup\n## Setup-1\n## Setup\n";
+        let ids: Vec<_> = extract_toc(markdown).into_iter().map(|e| e.id).collect();
+
+        assert_eq!(ids, vec!["setup", "setup-1", "setup-1-1", "setup-2"]);
+    }
+
+    #[test]
+    fn test_explicit_ids_are_kept_and_reserved() {
+        let markdown = "## Intro\n## Overview {#intro}\n";
+        let toc = extract_toc(markdown);
+
+        assert_eq!(toc[0].id, "intro-1");
+        assert_eq!(toc[1].id, "intro");
+        assert_eq!(toc[1].text, "Overview");
+    }
+
+    #[test]
+    fn test_anchor_headings_matches_toc() {
+        let markdown = "# Title\n## Getting `cargo` Started\nText\n## Getting `cargo` Started\n";
+        let (events, entries) = anchor_headings(Parser::new_ext(markdown, Options::all()));
+
+        let mut html = String::new();
+        pulldown_cmark::html::push_html(&mut html, events.into_iter());
+
+        for entry in &entries {
+            assert!(html.contains(&format!("id=\"{}\"", entry.id)));
+            assert!(html.contains(&format!("href=\"#{}\"", entry.id)));
+        }
+        assert_eq!(entries[1].id, "getting-cargo-started");
+        assert_eq!(entries[2].id, "getting-cargo-started-1");
+        assert!(html.contains("aria-label=\"Link to this section\""));
+    }
+
     #[test]
     fn test_render_toc() {
         let entries = vec![
@@ -157,4 +256,12 @@ More text.
         assert!(html.contains("toc-level-2"));
         assert!(html.contains("toc-level-3"));
     }
+
+    #[test]
+    fn test_render_toc_escapes_explicit_ids() {
+        let markdown = "## Quoted {#a\"onmouseover=\"x}\n";
+        let html = render_toc(&extract_toc(markdown));
+
+        assert!(html.contains("href=\"#a&quot;onmouseover=&quot;x\""));
+    }
 }
@@ -0,0 +1,190 @@
+//! Command-line arguments
+
+use std::path::PathBuf;
+
+/// Output directory for `build` when `--out` is not given
+const DEFAULT_OUT_DIR: &str = "dist";
+
+pub const USAGE: &str = "Usage:
+    blog-server [options]                       Run the server
+    blog-server build [--out <dir>] [options]   Render the site to static files (default: dist)
+
+Options:
+    --config <file>       Read settings from <file> instead of blog.toml
+    --set <key>=<value>   Override any setting, e.g. --set feeds.items=50
+    --host <host>         Same as --set server.host=<host>
+    --port <port>         Same as --set server.port=<port>
+    --base-url <url>      Same as --set site.base_url=<url>
+    --drafts              Same as --set content.enable_drafts=true
+    --watch               Same as --set server.watch=true
+    --print-config        Print the effective configuration and exit
+    -h, --help            Print this message and exit";
+
+/// What the binary was asked to do
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Run the HTTP server
+    Serve,
+    /// Render every route to files under `out`
+    Build { out: PathBuf },
+}
+
+/// Parsed command line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cli {
+    pub command: Command,
+    /// Config file given with `--config`
+    pub config_file: Option<PathBuf>,
+    /// Settings given on the command line, as `(key, value)`
+    pub overrides: Vec<(String, String)>,
+    /// Print the merged configuration instead of running
+    pub print_config: bool,
+    /// Print usage instead of running
+    pub help: bool,
+}
+
+impl Cli {
+    /// Parse the arguments following the program name
+    pub fn parse(args: impl IntoIterator<Item = String>) -> anyhow::Result<Self> {
+        let mut args = args.into_iter();
+        let mut command = None;
+        let mut out = None;
+        let mut cli = Self {
+            command: Command::Serve,
+            config_file: None,
+            overrides: Vec::new(),
+            print_config: false,
+            help: false,
+        };
+
+        while let Some(arg) = args.next() {
+            // `--flag=value` and `--flag value` are both accepted
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => {
+                    (flag.to_string(), Some(value.to_string()))
+                }
+                _ => (arg, None),
+            };
+            let mut value = || {
+                inline
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| anyhow::anyhow!("{} needs a value\n\n{}", flag, USAGE))
+            };
+
+            match flag.as_str() {
+                "serve" if command.is_none() => command = Some(flag),
+                "build" if command.is_none() => command = Some(flag),
+                "--out" => out = Some(PathBuf::from(value()?)),
+                "--config" => cli.config_file = Some(PathBuf::from(value()?)),
+                "--set" => {
+                    let setting = value()?;
+                    let Some((key, value)) = setting.split_once('=') else {
+                        anyhow::bail!("--set needs <key>=<value>, got {:?}\n\n{}", setting, USAGE);
+                    };
+                    cli.overrides
+                        .push((key.trim().to_string(), value.to_string()));
+                }
+                "--host" => cli.set("server.host", value()?),
+                "--port" => cli.set("server.port", value()?),
+                "--base-url" => cli.set("site.base_url", value()?),
+                "--drafts" if inline.is_none() => cli.set("content.enable_drafts", "true"),
+                "--watch" if inline.is_none() => cli.set("server.watch", "true"),
+                "--print-config" if inline.is_none() => cli.print_config = true,
+                "--help" | "-h" if inline.is_none() => cli.help = true,
+                _ if command.is_none() && !flag.starts_with('-') => {
+                    anyhow::bail!("Unknown command {:?}\n\n{}", flag, USAGE)
+                }
+                _ => anyhow::bail!("Unexpected argument {:?}\n\n{}", flag, USAGE),
+            }
+        }
+
+        cli.command = match command.as_deref() {
+            Some("build") => Command::Build {
+                out: out.unwrap_or_else(|| PathBuf::from(DEFAULT_OUT_DIR)),
+            },
+            _ if out.is_some() => anyhow::bail!("--out only applies to build\n\n{}", USAGE),
+            _ => Command::Serve,
+        };
+
+        Ok(cli)
+    }
+
+    fn set(&mut self, key: &str, value: impl Into<String>) {
+        self.overrides.push((key.to_string(), value.into()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> anyhow::Result<Cli> {
+        Cli::parse(args.iter().map(|a| a.to_string()))
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(parse(&[]).unwrap().command, Command::Serve);
+        assert_eq!(parse(&["serve"]).unwrap().command, Command::Serve);
+        assert_eq!(
+            parse(&["build"]).unwrap().command,
+            Command::Build {
+                out: PathBuf::from("dist")
+            }
+        );
+        assert_eq!(
+            parse(&["build", "--out", "public/"]).unwrap().command,
+            Command::Build {
+                out: PathBuf::from("public/")
+            }
+        );
+        assert_eq!(
+            parse(&["--out=site", "build"]).unwrap().command,
+            Command::Build {
+                out: PathBuf::from("site")
+            }
+        );
+
+        assert!(parse(&["build", "--out"]).is_err());
+        assert!(parse(&["build", "--verbose"]).is_err());
+        assert!(parse(&["serve", "--out", "dist"]).is_err());
+        assert!(parse(&["deploy"]).is_err());
+        assert!(parse(&["build", "build"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_config_flags() {
+        let cli = parse(&[
+            "--config",
+            "site.toml",
+            "--port=8080",
+            "--set",
+            "feeds.items=50",
+            "--drafts",
+            "--print-config",
+        ])
+        .unwrap();
+
+        assert_eq!(cli.config_file, Some(PathBuf::from("site.toml")));
+        assert!(cli.print_config);
+        assert_eq!(
+            cli.overrides,
+            vec![
+                ("server.port".to_string(), "8080".to_string()),
+                ("feeds.items".to_string(), "50".to_string()),
+                ("content.enable_drafts".to_string(), "true".to_string()),
+            ]
+        );
+
+        assert!(parse(&["--set", "feeds.items"]).is_err());
+        assert!(parse(&["--drafts=false"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_help() {
+        assert!(parse(&["--help"]).unwrap().help);
+        assert!(parse(&["build", "-h"]).unwrap().help);
+        assert!(!parse(&["build"]).unwrap().help);
+    }
+}
@@ -1,7 +1,61 @@
-//! Configuration management with environment variable support
+//! Configuration from `blog.toml`, environment variables and command-line flags
+//!
+//! Each source overrides the one before it: built-in defaults, then the
+//! config file, then `BLOG_*` environment variables, then flags. Every
+//! setting has one key, such as `server.port`, that names it in all three.
 
 use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use anyhow::Context as _;
+use chrono::{DateTime, LocalResult, NaiveDateTime, Offset as _, TimeDelta, TimeZone as _, Utc};
+use chrono_tz::Tz;
+use serde::Serialize;
+use toml::Value;
+
+/// Config file read from the working directory when none is given
+const DEFAULT_CONFIG_FILE: &str = "blog.toml";
+
+/// Every setting, by its `section.name` key, with its environment variable
+const SETTINGS: &[(&str, &str)] = &[
+    ("server.host", "BLOG_HOST"),
+    ("server.port", "BLOG_PORT"),
+    ("server.watch", "BLOG_WATCH"),
+    ("content.path", "BLOG_CONTENT_PATH"),
+    ("content.templates_path", "BLOG_TEMPLATES_PATH"),
+    ("content.static_path", "BLOG_STATIC_PATH"),
+    ("content.posts_per_page", "BLOG_POSTS_PER_PAGE"),
+    ("content.enable_drafts", "BLOG_ENABLE_DRAFTS"),
+    ("feeds.items", "BLOG_FEED_ITEMS"),
+    ("feeds.full_content", "BLOG_FEED_FULL_CONTENT"),
+    ("site.base_url", "BLOG_BASE_URL"),
+    ("site.title", "BLOG_SITE_TITLE"),
+    ("site.description", "BLOG_SITE_DESCRIPTION"),
+    ("site.tagline", "BLOG_SITE_TAGLINE"),
+    ("site.author", "BLOG_SITE_AUTHOR"),
+    ("site.language", "BLOG_SITE_LANGUAGE"),
+    ("site.timezone", "BLOG_SITE_TIMEZONE"),
+    ("rendering.highlight_code", "BLOG_HIGHLIGHT_CODE"),
+    ("rendering.heading_anchors", "BLOG_HEADING_ANCHORS"),
+    ("rendering.og_image_cache", "BLOG_OG_IMAGE_CACHE"),
+    ("rendering.og_image_fonts", "BLOG_OG_IMAGE_FONTS"),
+    ("rendering.export_cache", "BLOG_EXPORT_CACHE"),
+];
+
+/// Site-wide metadata, available to every template as `site`
+#[derive(Debug, Clone, Serialize)]
+pub struct SiteMeta {
+    /// Public URL without a trailing slash; prefix it to a path for an
+    /// absolute link, e.g. `{{ site.base_url }}/posts/{{ post.frontmatter.slug }}`
+    pub base_url: String,
+    pub title: String,
+    pub tagline: String,
+    pub description: String,
+    /// Default author, empty when there is none
+    pub author: String,
+    pub language: String,
+}
 
 /// Blog server configuration
 #[derive(Debug, Clone)]
@@ -20,6 +74,38 @@ pub struct Config {
     pub posts_per_page: usize,
     /// Whether to show draft posts
     pub enable_drafts: bool,
+    /// Public base URL used for absolute links in feeds
+    pub base_url: String,
+    /// Site name, used as the feed title
+    pub site_title: String,
+    /// One-line description of the site, used as the feed description
+    pub site_description: String,
+    /// Heading for the homepage when it is split into author sections
+    pub site_tagline: String,
+    /// Author credited on posts that name none; empty for no default
+    pub site_author: String,
+    /// Language of the site's content, as a BCP 47 tag such as `en`
+    pub site_language: String,
+    /// Timezone that post dates and publish times are written in
+    pub timezone: Tz,
+    /// Number of posts included in syndication feeds
+    pub feed_items: usize,
+    /// Whether feeds carry the full rendered post instead of the description
+    pub feed_full_content: bool,
+    /// Highlight fenced code blocks
+    pub highlight_code: bool,
+    /// Give headings ids and permalinks (needed for tables of contents)
+    pub heading_anchors: bool,
+    /// Directory generated preview images are cached in
+    pub og_image_cache: PathBuf,
+    /// Directory of extra fonts for generated preview images, read in
+    /// addition to the system fonts if it exists
+    pub og_image_fonts: PathBuf,
+    /// Directory static exports keep their manifest and render cache in, one
+    /// subdirectory per output directory
+    pub export_cache: PathBuf,
+    /// Watch content and templates for changes and reload them automatically
+    pub watch: bool,
 }
 
 impl Default for Config {
@@ -32,51 +118,237 @@ impl Default for Config {
             static_path: PathBuf::from("./static"),
             posts_per_page: 10,
             enable_drafts: false,
+            base_url: "http://127.0.0.1:3311".to_string(),
+            site_title: "The Nousphere".to_string(),
+            site_description: "Thoughts on code, collaboration, and building software"
+                .to_string(),
+            site_tagline: "The Nousphere in Dialogue".to_string(),
+            site_author: String::new(),
+            site_language: "en".to_string(),
+            timezone: Tz::UTC,
+            feed_items: 20,
+            feed_full_content: false,
+            highlight_code: true,
+            heading_anchors: true,
+            og_image_cache: PathBuf::from("./.cache/og"),
+            og_image_fonts: PathBuf::from("./fonts"),
+            export_cache: PathBuf::from("./.cache/export"),
+            watch: false,
         }
     }
 }
 
 impl Config {
-    /// Load configuration from environment variables with defaults
-    pub fn load() -> anyhow::Result<Self> {
+    /// Load configuration from the config file, environment and flag overrides
+    ///
+    /// `file` must exist when given. Otherwise `BLOG_CONFIG` names the file,
+    /// falling back to `blog.toml` in the working directory if there is one.
+    /// `overrides` are `(key, value)` pairs from the command line.
+    pub fn load(file: Option<&Path>, overrides: &[(String, String)]) -> anyhow::Result<Self> {
         let mut config = Self::default();
 
-        if let Ok(host) = env::var("BLOG_HOST") {
-            config.host = host;
+        let file = match file {
+            Some(file) => Some(file.to_path_buf()),
+            None => env::var("BLOG_CONFIG").map(PathBuf::from).ok(),
+        };
+        match file {
+            Some(file) => config.apply_file(&file)?,
+            None if Path::new(DEFAULT_CONFIG_FILE).exists() => {
+                config.apply_file(Path::new(DEFAULT_CONFIG_FILE))?
+            }
+            None => {}
         }
 
-        if let Ok(port) = env::var("BLOG_PORT") {
-            config.port = port.parse()?;
+        for (key, var) in SETTINGS {
+            if let Ok(value) = env::var(var) {
+                config.set(key, &Value::String(value), var)?;
+            }
         }
 
-        if let Ok(path) = env::var("BLOG_CONTENT_PATH") {
-            config.content_path = PathBuf::from(path);
+        for (key, value) in overrides {
+            config.set(key, &Value::String(value.clone()), "command line")?;
         }
 
-        if let Ok(path) = env::var("BLOG_TEMPLATES_PATH") {
-            config.templates_path = PathBuf::from(path);
+        Ok(config)
+    }
+
+    /// Apply every setting in a TOML config file
+    fn apply_file(&mut self, path: &Path) -> anyhow::Result<()> {
+        let source = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {:?}", path))?;
+        self.apply_toml(&source, &path.display().to_string())
+    }
+
+    /// Apply settings from TOML source, naming `origin` in errors
+    fn apply_toml(&mut self, source: &str, origin: &str) -> anyhow::Result<()> {
+        let table: toml::Table =
+            toml::from_str(source).with_context(|| format!("{}: invalid TOML", origin))?;
+
+        for (section, values) in &table {
+            let Value::Table(values) = values else {
+                anyhow::bail!("{}: `{}` must be a [{}] section", origin, section, section);
+            };
+            for (name, value) in values {
+                self.set(&format!("{}.{}", section, name), value, origin)?;
+            }
         }
 
-        if let Ok(path) = env::var("BLOG_STATIC_PATH") {
-            config.static_path = PathBuf::from(path);
+        Ok(())
+    }
+
+    /// Set one setting by key, naming the key and `origin` in any error
+    ///
+    /// Values from the environment and the command line arrive as strings and
+    /// are parsed; TOML values may also be of the setting's own type.
+    fn set(&mut self, key: &str, value: &Value, origin: &str) -> anyhow::Result<()> {
+        let result = match key {
+            "server.host" => string(value).map(|v| self.host = v),
+            "server.port" => parse(value, "a port number").map(|v| self.port = v),
+            "server.watch" => parse(value, "true or false").map(|v| self.watch = v),
+            "content.path" => string(value).map(|v| self.content_path = v.into()),
+            "content.templates_path" => string(value).map(|v| self.templates_path = v.into()),
+            "content.static_path" => string(value).map(|v| self.static_path = v.into()),
+            "content.posts_per_page" => parse(value, "a positive number")
+                .and_then(positive)
+                .map(|v| self.posts_per_page = v),
+            "content.enable_drafts" => {
+                parse(value, "true or false").map(|v| self.enable_drafts = v)
+            }
+            "feeds.items" => parse(value, "a number").map(|v| self.feed_items = v),
+            "feeds.full_content" => {
+                parse(value, "true or false").map(|v| self.feed_full_content = v)
+            }
+            "site.base_url" => {
+                string(value).map(|v| self.base_url = v.trim_end_matches('/').to_string())
+            }
+            "site.title" => string(value).map(|v| self.site_title = v),
+            "site.description" => string(value).map(|v| self.site_description = v),
+            "site.tagline" => string(value).map(|v| self.site_tagline = v),
+            "site.author" => string(value).map(|v| self.site_author = v.trim().to_string()),
+            "site.language" => string(value).map(|v| self.site_language = v),
+            "site.timezone" => {
+                parse(value, "a timezone name such as Europe/Berlin").map(|v| self.timezone = v)
+            }
+            "rendering.highlight_code" => {
+                parse(value, "true or false").map(|v| self.highlight_code = v)
+            }
+            "rendering.heading_anchors" => {
+                parse(value, "true or false").map(|v| self.heading_anchors = v)
+            }
+            "rendering.og_image_cache" => string(value).map(|v| self.og_image_cache = v.into()),
+            "rendering.og_image_fonts" => string(value).map(|v| self.og_image_fonts = v.into()),
+            "rendering.export_cache" => string(value).map(|v| self.export_cache = v.into()),
+            _ => Err(format!(
+                "unknown setting (expected one of {})",
+                SETTINGS
+                    .iter()
+                    .map(|(key, _)| *key)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+        };
+
+        result.map_err(|e| anyhow::anyhow!("{}: `{}`: {}", origin, key, e))
+    }
+
+    /// Current value of a setting, by key
+    fn get(&self, key: &str) -> Value {
+        let path = |path: &Path| Value::String(path.display().to_string());
+        match key {
+            "server.host" => Value::String(self.host.clone()),
+            "server.port" => Value::Integer(self.port.into()),
+            "server.watch" => Value::Boolean(self.watch),
+            "content.path" => path(&self.content_path),
+            "content.templates_path" => path(&self.templates_path),
+            "content.static_path" => path(&self.static_path),
+            "content.posts_per_page" => Value::Integer(self.posts_per_page as i64),
+            "content.enable_drafts" => Value::Boolean(self.enable_drafts),
+            "feeds.items" => Value::Integer(self.feed_items as i64),
+            "feeds.full_content" => Value::Boolean(self.feed_full_content),
+            "site.base_url" => Value::String(self.base_url.clone()),
+            "site.title" => Value::String(self.site_title.clone()),
+            "site.description" => Value::String(self.site_description.clone()),
+            "site.tagline" => Value::String(self.site_tagline.clone()),
+            "site.author" => Value::String(self.site_author.clone()),
+            "site.language" => Value::String(self.site_language.clone()),
+            "site.timezone" => Value::String(self.timezone.name().to_string()),
+            "rendering.highlight_code" => Value::Boolean(self.highlight_code),
+            "rendering.heading_anchors" => Value::Boolean(self.heading_anchors),
+            "rendering.og_image_cache" => path(&self.og_image_cache),
+            "rendering.og_image_fonts" => path(&self.og_image_fonts),
+            "rendering.export_cache" => path(&self.export_cache),
+            _ => unreachable!("unknown setting {}", key),
         }
+    }
 
-        if let Ok(count) = env::var("BLOG_POSTS_PER_PAGE") {
-            config.posts_per_page = count.parse()?;
+    /// Site metadata for templates
+    pub fn site(&self) -> SiteMeta {
+        SiteMeta {
+            base_url: self.base_url.clone(),
+            title: self.site_title.clone(),
+            tagline: self.site_tagline.clone(),
+            description: self.site_description.clone(),
+            author: self.site_author.clone(),
+            language: self.site_language.clone(),
         }
+    }
+
+    /// Absolute URL for a site path such as `/posts/hello`
+    pub fn absolute_url(&self, path: &str) -> String {
+        format!(
+            "{}/{}",
+            self.base_url.trim_end_matches('/'),
+            path.trim_start_matches('/')
+        )
+    }
+
+    /// Current wall-clock time in the site's timezone, comparable with post
+    /// dates
+    pub fn now(&self) -> NaiveDateTime {
+        Utc::now().with_timezone(&self.timezone).naive_local()
+    }
 
-        if let Ok(enable) = env::var("BLOG_ENABLE_DRAFTS") {
-            config.enable_drafts = enable.parse().unwrap_or(false);
+    /// A post date or publish time as a point in time, read in the site's
+    /// timezone
+    ///
+    /// Times repeated by a daylight saving change are taken as their first
+    /// occurrence. Times it skips are read with the offset in force just
+    /// before, which moves them forward by the length of the gap, so `02:30`
+    /// on a night the clocks jump from 02:00 to 03:00 becomes `03:30`.
+    pub fn localize(&self, dt: NaiveDateTime) -> DateTime<Tz> {
+        match self.timezone.from_local_datetime(&dt) {
+            LocalResult::Single(local) | LocalResult::Ambiguous(local, _) => local,
+            LocalResult::None => {
+                let offset = self
+                    .timezone
+                    .offset_from_local_datetime(&(dt - TimeDelta::days(1)))
+                    .earliest()
+                    .map_or(0, |offset| offset.fix().local_minus_utc());
+                let utc = dt - TimeDelta::seconds(offset.into());
+                self.timezone.from_utc_datetime(&utc)
+            }
         }
+    }
 
-        // Validate paths exist
-        config.validate()?;
+    /// The effective configuration as `blog.toml` source
+    pub fn to_toml(&self) -> anyhow::Result<String> {
+        let mut table = toml::Table::new();
+        for (key, _) in SETTINGS {
+            let (section, name) = key.split_once('.').expect("keys are section.name");
+            let Value::Table(section) = table
+                .entry(section)
+                .or_insert_with(|| Value::Table(toml::Table::new()))
+            else {
+                unreachable!("sections are tables");
+            };
+            section.insert(name.to_string(), self.get(key));
+        }
 
-        Ok(config)
+        Ok(toml::to_string_pretty(&table)?)
     }
 
     /// Validate that required paths exist
-    fn validate(&self) -> anyhow::Result<()> {
+    pub fn validate(&self) -> anyhow::Result<()> {
         if !self.content_path.exists() {
             tracing::warn!(
                 "Content path {:?} does not exist, creating...",
@@ -105,3 +377,155 @@ impl Config {
         Ok(())
     }
 }
+
+/// A string setting; anything else is an error rather than being converted
+fn string(value: &Value) -> Result<String, String> {
+    match value {
+        Value::String(s) => Ok(s.clone()),
+        other => Err(format!("expected a string, got {}", other)),
+    }
+}
+
+/// A setting parsed from a string, or given directly as a TOML scalar
+fn parse<T: FromStr>(value: &Value, expected: &str) -> Result<T, String> {
+    let text = match value {
+        Value::String(s) => s.trim().to_string(),
+        Value::Integer(i) => i.to_string(),
+        Value::Boolean(b) => b.to_string(),
+        other => return Err(format!("expected {}, got {}", expected, other)),
+    };
+    text.parse()
+        .map_err(|_| format!("expected {}, got {:?}", expected, text))
+}
+
+fn positive(count: usize) -> Result<usize, String> {
+    if count == 0 {
+        Err("must be at least 1".to_string())
+    } else {
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toml_sections() {
+        let mut config = Config::default();
+        config
+            .apply_toml(
+                r#"
+                [server]
+                port = 8080
+
+                [content]
+                enable_drafts = true
+
+                [site]
+                base_url = "https://example.com/"
+                title = "Example"
+                "#,
+                "blog.toml",
+            )
+            .unwrap();
+
+        assert_eq!(config.port, 8080);
+        assert!(config.enable_drafts);
+        assert_eq!(config.base_url, "https://example.com");
+        assert_eq!(config.site_title, "Example");
+    }
+
+    #[test]
+    fn test_later_sources_override() {
+        let mut config = Config::default();
+        config
+            .apply_toml("[server]\nport = 8080\n", "blog.toml")
+            .unwrap();
+        config
+            .set("server.port", &Value::String("9090".into()), "BLOG_PORT")
+            .unwrap();
+        assert_eq!(config.port, 9090);
+    }
+
+    #[test]
+    fn test_errors_name_the_key() {
+        let mut config = Config::default();
+
+        let err = config
+            .set(
+                "content.enable_drafts",
+                &Value::String("yes".into()),
+                "BLOG_ENABLE_DRAFTS",
+            )
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("BLOG_ENABLE_DRAFTS"), "{}", err);
+        assert!(err.contains("`content.enable_drafts`"), "{}", err);
+
+        let err = config
+            .apply_toml("[server]\nprot = 80\n", "blog.toml")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("`server.prot`"), "{}", err);
+
+        let err = config
+            .apply_toml("[content]\nposts_per_page = 0\n", "blog.toml")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("`content.posts_per_page`"), "{}", err);
+
+        assert!(config.apply_toml("server = 1\n", "blog.toml").is_err());
+        assert!(config.apply_toml("[site]\ntitle = 3\n", "blog.toml").is_err());
+    }
+
+    #[test]
+    fn test_printed_config_round_trips() {
+        let config = Config {
+            port: 4000,
+            feed_full_content: true,
+            ..Config::default()
+        };
+
+        let mut reloaded = Config::default();
+        reloaded
+            .apply_toml(&config.to_toml().unwrap(), "printed")
+            .unwrap();
+        assert_eq!(reloaded.port, 4000);
+        assert!(reloaded.feed_full_content);
+        assert_eq!(reloaded.to_toml().unwrap(), config.to_toml().unwrap());
+    }
+
+    #[test]
+    fn test_timezone() {
+        let mut config = Config::default();
+        config
+            .apply_toml("[site]\ntimezone = \"America/New_York\"\n", "blog.toml")
+            .unwrap();
+        assert_eq!(config.timezone, chrono_tz::America::New_York);
+        assert!(config.to_toml().unwrap().contains("timezone = \"America/New_York\""));
+
+        let dt = NaiveDateTime::parse_from_str("2025-01-02 09:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        assert_eq!(config.localize(dt).to_rfc3339(), "2025-01-02T09:00:00-05:00");
+
+        // Skipped by the switch to daylight saving time
+        let dt = NaiveDateTime::parse_from_str("2025-03-09 02:30:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        assert_eq!(config.localize(dt).to_rfc3339(), "2025-03-09T03:30:00-04:00");
+
+        let err = config
+            .set("site.timezone", &Value::String("Mars/Olympus".into()), "BLOG_SITE_TIMEZONE")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("`site.timezone`"), "{}", err);
+    }
+
+    #[test]
+    fn test_absolute_url() {
+        let config = Config {
+            base_url: "https://example.com".to_string(),
+            ..Config::default()
+        };
+        assert_eq!(config.absolute_url("/posts/a"), "https://example.com/posts/a");
+        assert_eq!(config.absolute_url(""), "https://example.com/");
+    }
+}
@@ -0,0 +1,312 @@
+//! What each exported file is built from
+//!
+//! Every output records the posts, pages, templates and other inputs its
+//! handler reads. Hashing those inputs gives a fingerprint that only changes
+//! when the output could, so later exports can leave the file alone.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use blog_content::cache::ContentHasher;
+use blog_content::{Page, Post};
+
+use crate::config::Config;
+
+/// One input an exported file is rendered from
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Dependency {
+    /// A single template file, by name relative to the templates directory
+    Template(String),
+    Post(String),
+    Page(String),
+    /// `authors.yaml` and `categories.yaml`
+    Registry,
+    /// Every post, for outputs such as feeds and indexes that list them all
+    AllPosts,
+    /// Every static page
+    AllPages,
+    /// Position in a paginated listing, which decides its previous/next links
+    Pagination {
+        page: usize,
+        total: usize,
+    },
+    /// A file copied verbatim from the given source path, or a directory of
+    /// files read by the handler, such as preview image fonts
+    Asset(PathBuf),
+}
+
+impl fmt::Display for Dependency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Template(name) => write!(f, "template:{}", name),
+            Self::Post(slug) => write!(f, "post:{}", slug),
+            Self::Page(slug) => write!(f, "page:{}", slug),
+            Self::Registry => f.write_str("registry"),
+            Self::AllPosts => f.write_str("posts"),
+            Self::AllPages => f.write_str("pages"),
+            Self::Pagination { page, total } => write!(f, "pagination:{}/{}", page, total),
+            Self::Asset(path) => write!(f, "asset:{}", path.display()),
+        }
+    }
+}
+
+/// Templates and the templates they extend or include
+#[derive(Debug, Default)]
+pub struct TemplateGraph {
+    /// Hash of each template's source
+    hashes: HashMap<String, u64>,
+    /// Direct `extends`, `include` and `import` targets of each template
+    edges: HashMap<String, Vec<String>>,
+}
+
+impl TemplateGraph {
+    /// Read every `.html` template under `dir`
+    pub fn load(dir: &Path) -> anyhow::Result<Self> {
+        let mut graph = Self::default();
+
+        for entry in walkdir::WalkDir::new(dir) {
+            let entry = entry?;
+            if !entry.file_type().is_file()
+                || entry.path().extension().and_then(|e| e.to_str()) != Some("html")
+            {
+                continue;
+            }
+
+            let name = entry
+                .path()
+                .strip_prefix(dir)?
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            let source = std::fs::read_to_string(entry.path())?;
+            graph.insert(name, &source);
+        }
+
+        Ok(graph)
+    }
+
+    fn insert(&mut self, name: String, source: &str) {
+        let mut hasher = ContentHasher::new();
+        hasher.write_str(source);
+        self.hashes.insert(name.clone(), hasher.finish());
+        self.edges.insert(name, referenced_templates(source));
+    }
+
+    /// `name` and every template it pulls in, directly or not
+    pub fn closure(&self, name: &str) -> BTreeSet<String> {
+        let mut seen = BTreeSet::new();
+        let mut pending = vec![name.to_string()];
+
+        while let Some(name) = pending.pop() {
+            if let Some(edges) = self.edges.get(&name) {
+                pending.extend(edges.iter().filter(|e| !seen.contains(*e)).cloned());
+            }
+            seen.insert(name);
+        }
+
+        seen
+    }
+
+    /// Template dependencies of an output rendered with `name`
+    pub fn dependencies(&self, name: &str) -> impl Iterator<Item = Dependency> {
+        self.closure(name).into_iter().map(Dependency::Template)
+    }
+}
+
+/// Names of the templates a Tera source extends, includes or imports
+///
+/// Only literal names are found, which is all the templates here use.
+fn referenced_templates(source: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut rest = source;
+
+    while let Some(start) = rest.find("{%") {
+        rest = &rest[start + 2..];
+        let Some(end) = rest.find("%}") else {
+            break;
+        };
+        let tag = rest[..end].trim_matches(|c: char| c == '-' || c.is_whitespace());
+        rest = &rest[end + 2..];
+
+        let Some((keyword, args)) = tag.split_once(char::is_whitespace) else {
+            continue;
+        };
+        if !matches!(keyword, "extends" | "include" | "import") {
+            continue;
+        }
+
+        let args = args.trim_start();
+        let Some(quote) = args.chars().next().filter(|c| matches!(c, '"' | '\'')) else {
+            continue;
+        };
+        let Some((name, rest)) = args[1..].split_once(quote) else {
+            continue;
+        };
+        // Skip names built with `~`; anything else after a literal is `as`
+        // or `ignore missing`
+        if !rest.trim_start().starts_with('~') {
+            names.push(name.to_string());
+        }
+    }
+
+    names
+}
+
+/// Current hash of every input, for fingerprinting outputs
+pub struct Fingerprints<'a> {
+    /// Settings and program version, which can change any output
+    build: u64,
+    posts: HashMap<&'a str, u64>,
+    all_posts: u64,
+    pages: HashMap<&'a str, u64>,
+    all_pages: u64,
+    registry: u64,
+    templates: &'a TemplateGraph,
+}
+
+impl<'a> Fingerprints<'a> {
+    pub fn new(
+        config: &Config,
+        posts: &'a [Post],
+        pages: &'a [Page],
+        templates: &'a TemplateGraph,
+    ) -> Self {
+        let mut build = ContentHasher::new();
+        build.write_str(env!("CARGO_PKG_VERSION"));
+        build.write_str(&format!("{:?}", config));
+
+        let posts: HashMap<_, _> = posts
+            .iter()
+            .map(|post| (post.slug(), hash_json(post)))
+            .collect();
+        let pages: HashMap<_, _> = pages
+            .iter()
+            .map(|page| (page.slug.as_str(), hash_json(page)))
+            .collect();
+
+        let mut registry = ContentHasher::new();
+        for file in ["authors.yaml", "categories.yaml"] {
+            let source = std::fs::read(config.content_path.join(file)).unwrap_or_default();
+            registry.write(&(source.len() as u64).to_le_bytes());
+            registry.write(&source);
+        }
+
+        Self {
+            build: build.finish(),
+            all_posts: hash_all(&posts),
+            posts,
+            all_pages: hash_all(&pages),
+            pages,
+            registry: registry.finish(),
+            templates,
+        }
+    }
+
+    /// Hash of an output built from `deps`
+    ///
+    /// The dependency list itself is part of the hash, so an output whose
+    /// inputs changed membership (say, a listing page that gained a post) is
+    /// rebuilt even if every individual input is unchanged.
+    pub fn of(&self, deps: &[Dependency]) -> u64 {
+        let mut hasher = ContentHasher::new();
+        hasher.write(&self.build.to_le_bytes());
+
+        for dep in deps {
+            hasher.write_str(&dep.to_string());
+            // Missing inputs hash as zero; the handler decides what that means
+            let hash = match dep {
+                Dependency::Template(name) => self.templates.hashes.get(name.as_str()).copied(),
+                Dependency::Post(slug) => self.posts.get(slug.as_str()).copied(),
+                Dependency::Page(slug) => self.pages.get(slug.as_str()).copied(),
+                Dependency::Registry => Some(self.registry),
+                Dependency::AllPosts => Some(self.all_posts),
+                Dependency::AllPages => Some(self.all_pages),
+                Dependency::Pagination { .. } => None,
+                Dependency::Asset(path) => hash_files(path),
+            };
+            hasher.write(&hash.unwrap_or_default().to_le_bytes());
+        }
+
+        hasher.finish()
+    }
+}
+
+/// Hash of a file, or of every file under a directory with its relative
+/// path; `None` if there is nothing at `path`
+fn hash_files(path: &Path) -> Option<u64> {
+    if !path.is_dir() {
+        let bytes = std::fs::read(path).ok()?;
+        let mut file = ContentHasher::new();
+        file.write(&bytes);
+        return Some(file.finish());
+    }
+
+    let mut hasher = ContentHasher::new();
+    for entry in walkdir::WalkDir::new(path).sort_by_file_name() {
+        let Ok(entry) = entry else { continue };
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let bytes = std::fs::read(entry.path()).unwrap_or_default();
+        let name = entry.path().strip_prefix(path).unwrap_or(entry.path());
+        hasher.write_str(&name.to_string_lossy());
+        hasher.write(&(bytes.len() as u64).to_le_bytes());
+        hasher.write(&bytes);
+    }
+    Some(hasher.finish())
+}
+
+fn hash_json(value: &impl serde::Serialize) -> u64 {
+    let mut hasher = ContentHasher::new();
+    hasher.write_str(&serde_json::to_string(value).unwrap_or_default());
+    hasher.finish()
+}
+
+/// Order-independent hash of a set of keyed hashes
+fn hash_all(hashes: &HashMap<&str, u64>) -> u64 {
+    let mut keys: Vec<_> = hashes.keys().collect();
+    keys.sort();
+
+    let mut hasher = ContentHasher::new();
+    for key in keys {
+        hasher.write_str(key);
+        hasher.write(&hashes[key].to_le_bytes());
+    }
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_referenced_templates() {
+        let source = r#"{% extends "base.html" %}
+            {% block content %}{%- include 'partials/byline.html' -%}
+            {% include "partials/" ~ name %}{% import "macros.html" as m %}{% endblock %}"#;
+        assert_eq!(
+            referenced_templates(source),
+            vec!["base.html", "partials/byline.html", "macros.html"]
+        );
+    }
+
+    #[test]
+    fn test_closure_follows_includes() {
+        let mut graph = TemplateGraph::default();
+        graph.insert("post.html".to_string(), r#"{% extends "base.html" %}"#);
+        graph.insert(
+            "base.html".to_string(),
+            r#"{% include "partials/header.html" %}"#,
+        );
+        graph.insert("partials/header.html".to_string(), "<nav></nav>");
+        graph.insert("tags.html".to_string(), r#"{% extends "base.html" %}"#);
+
+        let closure: Vec<_> = graph.closure("post.html").into_iter().collect();
+        assert_eq!(
+            closure,
+            vec!["base.html", "partials/header.html", "post.html"]
+        );
+    }
+}
@@ -0,0 +1,114 @@
+//! Record of a previous export, kept in the export's cache directory
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context as _;
+use serde::{Deserialize, Serialize};
+
+/// File name of the manifest inside the cache directory
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// File name of the saved render cache inside the cache directory
+pub const RENDER_CACHE_FILE: &str = "render-cache.json";
+
+/// Names earlier exports gave these files, inside the output directory
+pub const LEGACY_FILES: [&str; 2] = [".export-manifest.json", ".render-cache.json"];
+
+/// Bumped whenever the manifest layout changes, discarding older manifests
+const VERSION: u32 = 1;
+
+/// Every file an export wrote and what it was built from
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Manifest {
+    version: u32,
+    /// Output files by path relative to the output directory
+    pub files: BTreeMap<PathBuf, ManifestEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// Hash of the inputs the file was built from
+    pub fingerprint: u64,
+    /// The inputs, for reading the manifest by hand
+    pub deps: Vec<String>,
+}
+
+impl Manifest {
+    pub fn new() -> Self {
+        Self {
+            version: VERSION,
+            files: BTreeMap::new(),
+        }
+    }
+
+    /// The manifest left in `cache` by the last export, or an empty one
+    ///
+    /// An unreadable or outdated manifest means a full rebuild, never an
+    /// error.
+    pub fn load(cache: &Path) -> Self {
+        let path = cache.join(MANIFEST_FILE);
+        let Ok(json) = std::fs::read_to_string(&path) else {
+            return Self::new();
+        };
+
+        match serde_json::from_str::<Self>(&json) {
+            Ok(manifest) if manifest.version == VERSION => manifest,
+            Ok(_) => {
+                tracing::info!(
+                    "Export manifest {:?} is outdated, rebuilding everything",
+                    path
+                );
+                Self::new()
+            }
+            Err(e) => {
+                tracing::warn!("Ignoring unreadable export manifest {:?}: {}", path, e);
+                Self::new()
+            }
+        }
+    }
+
+    pub fn save(&self, cache: &Path) -> anyhow::Result<()> {
+        let path = cache.join(MANIFEST_FILE);
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(&path, json).with_context(|| format!("Failed to write {:?}", path))
+    }
+
+    /// Whether `file` was written from inputs with this fingerprint
+    pub fn is_current(&self, file: &Path, fingerprint: u64) -> bool {
+        self.files
+            .get(file)
+            .is_some_and(|entry| entry.fingerprint == fingerprint)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_and_version() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(Manifest::load(dir.path()).files.is_empty());
+
+        let mut manifest = Manifest::new();
+        manifest.files.insert(
+            PathBuf::from("index.html"),
+            ManifestEntry {
+                fingerprint: 42,
+                deps: vec!["template:index.html".to_string()],
+            },
+        );
+        manifest.save(dir.path()).unwrap();
+
+        let loaded = Manifest::load(dir.path());
+        assert!(loaded.is_current(Path::new("index.html"), 42));
+        assert!(!loaded.is_current(Path::new("index.html"), 7));
+        assert!(!loaded.is_current(Path::new("tags/index.html"), 42));
+
+        let outdated =
+            r#"{"version": 0, "files": {"index.html": {"fingerprint": 42, "deps": []}}}"#;
+        std::fs::write(dir.path().join(MANIFEST_FILE), outdated).unwrap();
+        assert!(Manifest::load(dir.path()).files.is_empty());
+    }
+}
@@ -0,0 +1,531 @@
+//! Static site export
+//!
+//! Every route is rendered through the same router the server uses and
+//! written to a file, so the output can be published to plain object storage.
+//!
+//! Exports are incremental: a manifest records what each file was built from,
+//! and a later export into the same directory only rewrites files whose inputs
+//! changed and removes files that no longer belong to the site. The manifest
+//! is kept under `config.export_cache` rather than in the output, so it is
+//! never published.
+
+mod deps;
+mod manifest;
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context as _;
+use axum::{body::Body, http::Request, http::StatusCode, Router};
+use blog_content::cache::ContentHasher;
+use blog_content::{Page, RenderSnapshot, TimelineScope};
+use tower::ServiceExt;
+
+use crate::config::Config;
+use crate::routes::posts::{encode_query_value, ListQuery};
+use crate::AppState;
+use deps::{Dependency, Fingerprints, TemplateGraph};
+use manifest::{Manifest, ManifestEntry, LEGACY_FILES, RENDER_CACHE_FILE};
+
+/// A route to render and the file it is written to, relative to the output
+/// directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportRoute {
+    pub url: String,
+    pub file: PathBuf,
+    /// Inputs the handler reads to render this route
+    pub deps: Vec<Dependency>,
+}
+
+impl ExportRoute {
+    /// An HTML page, written as `<path>/index.html` so that it is served for
+    /// the extensionless URL
+    ///
+    /// Every page's navigation lists the registered authors, so every page
+    /// depends on the registry.
+    fn page(segments: &[&str]) -> Self {
+        let mut route = Self::file(segments).depends_on([Dependency::Registry]);
+        route.file.push("index.html");
+        route
+    }
+
+    /// A file such as `rss.xml`, written to exactly its URL path
+    fn file(segments: &[&str]) -> Self {
+        let url = format!(
+            "/{}",
+            segments
+                .iter()
+                .map(|s| encode_query_value(s))
+                .collect::<Vec<_>>()
+                .join("/")
+        );
+
+        Self {
+            url,
+            file: segments.iter().collect(),
+            deps: Vec::new(),
+        }
+    }
+
+    fn depends_on(mut self, deps: impl IntoIterator<Item = Dependency>) -> Self {
+        self.deps.extend(deps);
+        self
+    }
+}
+
+/// Counts reported by an export
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExportStats {
+    /// Files rendered or copied because their inputs changed
+    pub written: usize,
+    /// Files left alone because their inputs did not change
+    pub unchanged: usize,
+    /// Files from an earlier export that no longer belong to the site
+    pub removed: usize,
+}
+
+/// Every route the site is made of, given the currently loaded content
+pub fn routes(state: &AppState, pages: &[Page], templates: &TemplateGraph) -> Vec<ExportRoute> {
+    let content = state.content();
+    let posts = &content.posts;
+    let registry = &content.registry;
+    let series = &content.series;
+    let timeline = &content.timeline;
+    let per_page = state.config.posts_per_page;
+
+    // The index renders each author's posts with that author's card template
+    let index_templates: BTreeSet<_> = std::iter::once("index.html")
+        .chain(registry.authors.iter().map(|a| a.card_template.as_str()))
+        .flat_map(|name| templates.closure(name))
+        .collect();
+
+    let every_post = || [Dependency::AllPosts, Dependency::Registry];
+    let mut routes = vec![
+        ExportRoute::page(&[])
+            .depends_on(index_templates.into_iter().map(Dependency::Template))
+            .depends_on(every_post()),
+        ExportRoute::page(&["authors"])
+            .depends_on(templates.dependencies("authors.html"))
+            .depends_on(every_post()),
+        ExportRoute::page(&["tags"])
+            .depends_on(templates.dependencies("tags.html"))
+            .depends_on([Dependency::AllPosts]),
+        // Results are found in the browser, so the page itself is empty
+        ExportRoute::page(&["search"]).depends_on(templates.dependencies("search.html")),
+        ExportRoute::file(&["search-index.json"]).depends_on([Dependency::AllPosts]),
+        ExportRoute::file(&["sitemap.xml"])
+            .depends_on(every_post())
+            .depends_on([Dependency::AllPages]),
+        ExportRoute::file(&["robots.txt"]),
+    ];
+
+    let feeds: [&[&str]; 6] = [
+        &["rss.xml"],
+        &["atom.xml"],
+        &["feed.json"],
+        &["posts", "feed.xml"],
+        &["posts", "rss.xml"],
+        &["posts", "feed.json"],
+    ];
+    routes.extend(
+        feeds
+            .iter()
+            .map(|segments| ExportRoute::file(segments).depends_on(every_post())),
+    );
+
+    // A listing at `base` plus `base/page/N` for each later page, each
+    // depending on just the posts shown on it
+    let mut listing = |base: &[&str], query: ListQuery| {
+        let matching: Vec<_> = posts.iter().filter(|p| query.matches(p)).collect();
+        let total = matching.len().div_ceil(per_page);
+
+        let listing_page = |segments: &[&str], page: usize| {
+            let window = matching.iter().skip((page - 1) * per_page).take(per_page);
+            ExportRoute::page(segments)
+                .depends_on(templates.dependencies("post_list.html"))
+                .depends_on([Dependency::Registry, Dependency::Pagination { page, total }])
+                .depends_on(window.map(|p| Dependency::Post(p.slug().to_string())))
+        };
+
+        routes.push(listing_page(base, 1));
+        for page in 2..=total {
+            let number = page.to_string();
+            let mut segments = base.to_vec();
+            segments.extend(["page", number.as_str()]);
+            routes.push(listing_page(&segments, page));
+        }
+    };
+
+    listing(&["posts"], ListQuery::default());
+    for author in registry.authors.iter() {
+        let query = ListQuery {
+            author: Some(author.id.clone()),
+            ..ListQuery::default()
+        };
+        listing(&["authors", &author.id], query);
+    }
+    for category in registry.categories.iter() {
+        let query = ListQuery {
+            category: Some(category.slug.clone()),
+            ..ListQuery::default()
+        };
+        listing(&["categories", &category.slug], query);
+    }
+    for tag in blog_content::tag_counts(posts.iter()) {
+        let query = ListQuery {
+            tag: Some(tag.tag.clone()),
+            ..ListQuery::default()
+        };
+        listing(&["tags", &tag.tag], query);
+    }
+
+    routes.extend(series.iter().map(|series| {
+        ExportRoute::page(&["series", &series.slug])
+            .depends_on(templates.dependencies("series.html"))
+            .depends_on([Dependency::Registry])
+            .depends_on(
+                series
+                    .parts
+                    .iter()
+                    .map(|p| Dependency::Post(p.slug.clone())),
+            )
+    }));
+
+    // A post shows its related posts, series and neighbours as well as itself
+    routes.extend(posts.iter().map(|post| {
+        let slug = post.slug();
+        let related = post.related_posts().iter().map(|r| r.slug.clone());
+        let similar = post
+            .similar_posts_by_tags(posts, 3)
+            .into_iter()
+            .map(|p| p.slug().to_string());
+        let series_parts: Vec<_> = series
+            .nav_for(post)
+            .and_then(|nav| series.get(&nav.slug))
+            .map(|series| series.parts.iter().map(|p| p.slug.clone()).collect())
+            .unwrap_or_default();
+        let adjacent: Vec<_> = timeline
+            .adjacent(&TimelineScope::All, slug)
+            .map(|adjacent| {
+                [&adjacent.prev, &adjacent.next]
+                    .into_iter()
+                    .flatten()
+                    .map(|p| p.slug.clone())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        ExportRoute::page(&["posts", slug])
+            .depends_on(templates.dependencies("post.html"))
+            .depends_on([Dependency::Registry, Dependency::Post(slug.to_string())])
+            .depends_on(
+                related
+                    .chain(similar)
+                    .chain(series_parts)
+                    .chain(adjacent)
+                    .map(Dependency::Post),
+            )
+    }));
+
+    // Posts without a featured image get a generated preview image, drawn
+    // from the post, its authors and category in the configured fonts
+    let fonts = Dependency::Asset(state.config.og_image_fonts.clone());
+    routes.extend(
+        posts
+            .iter()
+            .filter(|post| post.frontmatter.featured_image.is_none())
+            .map(|post| {
+                ExportRoute::file(&["posts", post.slug(), "og.png"]).depends_on([
+                    Dependency::Registry,
+                    Dependency::Post(post.slug().to_string()),
+                    fonts.clone(),
+                ])
+            }),
+    );
+
+    routes.extend(pages.iter().map(|page| {
+        ExportRoute::page(&["pages", &page.slug])
+            .depends_on(templates.dependencies("page.html"))
+            .depends_on([Dependency::Page(page.slug.clone())])
+    }));
+
+    routes
+}
+
+/// Directory an export into `out` keeps its manifest and render cache in
+///
+/// Each output directory gets its own, so exports to different places don't
+/// disturb each other.
+pub fn cache_dir(config: &Config, out: &Path) -> PathBuf {
+    let out = std::path::absolute(out).unwrap_or_else(|_| out.to_path_buf());
+    let mut hasher = ContentHasher::new();
+    hasher.write_str(&out.to_string_lossy());
+    let name = format!("{:016x}", hasher.finish());
+    config.export_cache.join(name)
+}
+
+/// The render cache saved by the last export into `out`, if any
+pub fn load_render_cache(config: &Config, out: &Path) -> Option<RenderSnapshot> {
+    let path = cache_dir(config, out).join(RENDER_CACHE_FILE);
+    let json = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&json)
+        .map_err(|e| tracing::warn!("Ignoring unreadable render cache: {}", e))
+        .ok()
+}
+
+/// Render every route into `out` and copy static assets and images alongside,
+/// skipping files whose inputs are unchanged since the last export
+pub async fn export(state: &AppState, router: Router, out: &Path) -> anyhow::Result<ExportStats> {
+    let cache = cache_dir(&state.config, out);
+    std::fs::create_dir_all(out)?;
+    std::fs::create_dir_all(&cache).with_context(|| format!("Failed to create {:?}", cache))?;
+
+    // Earlier exports kept their bookkeeping in the output itself
+    for file in LEGACY_FILES {
+        match std::fs::remove_file(out.join(file)) {
+            Ok(()) => tracing::info!("Removed {:?} from {:?}", file, out),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e).with_context(|| format!("Failed to remove {:?}", file)),
+        }
+    }
+
+    let pages = blog_content::load_all_pages(&state.config.content_path)?;
+    let templates = TemplateGraph::load(&state.config.templates_path)?;
+    let mut routes = routes(state, &pages, &templates);
+
+    let assets = [
+        (state.config.static_path.clone(), PathBuf::from("static")),
+        (
+            state.config.content_path.join("images"),
+            PathBuf::from("images"),
+        ),
+    ];
+    for (from, to) in &assets {
+        routes.extend(asset_routes(from, to)?);
+    }
+
+    let content = state.content();
+    let posts = &content.posts;
+    let fingerprints = Fingerprints::new(&state.config, posts, &pages, &templates);
+    let previous = Manifest::load(&cache);
+    let mut manifest = Manifest::new();
+    let mut stats = ExportStats::default();
+
+    for route in &routes {
+        let fingerprint = fingerprints.of(&route.deps);
+        let path = out.join(&route.file);
+
+        if previous.is_current(&route.file, fingerprint) && path.exists() {
+            stats.unchanged += 1;
+        } else {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            match route.deps.as_slice() {
+                [Dependency::Asset(source)] => {
+                    std::fs::copy(source, &path)
+                        .with_context(|| format!("Failed to copy {:?}", source))?;
+                }
+                _ => {
+                    let body = render(&router, &route.url).await?;
+                    std::fs::write(&path, body)
+                        .with_context(|| format!("Failed to write {:?}", path))?;
+                }
+            }
+            stats.written += 1;
+        }
+
+        let entry = ManifestEntry {
+            fingerprint,
+            deps: route.deps.iter().map(ToString::to_string).collect(),
+        };
+        manifest.files.insert(route.file.clone(), entry);
+    }
+
+    for file in previous.files.keys() {
+        if !manifest.files.contains_key(file) {
+            remove_output(out, file)?;
+            stats.removed += 1;
+        }
+    }
+
+    manifest.save(&cache)?;
+    let snapshot = serde_json::to_string(&content.render_cache.snapshot())?;
+    std::fs::write(cache.join(RENDER_CACHE_FILE), snapshot)?;
+
+    tracing::info!(
+        "Exported {} files into {:?} ({} written, {} unchanged, {} removed)",
+        routes.len(),
+        out,
+        stats.written,
+        stats.unchanged,
+        stats.removed
+    );
+    Ok(stats)
+}
+
+/// Response body for a GET request, failing on anything but `200 OK`
+async fn render(router: &Router, url: &str) -> anyhow::Result<axum::body::Bytes> {
+    let request = Request::get(url).body(Body::empty())?;
+    let response = router.clone().oneshot(request).await?;
+
+    if response.status() != StatusCode::OK {
+        anyhow::bail!("Rendering {} returned {}", url, response.status());
+    }
+
+    Ok(axum::body::to_bytes(response.into_body(), usize::MAX).await?)
+}
+
+/// A route per file under `from`, to be copied to the same place under `to`
+///
+/// A missing source directory has no files.
+fn asset_routes(from: &Path, to: &Path) -> anyhow::Result<Vec<ExportRoute>> {
+    if !from.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut routes = Vec::new();
+    for entry in walkdir::WalkDir::new(from) {
+        let entry = entry?;
+        if entry.file_type().is_dir() {
+            continue;
+        }
+
+        let file = to.join(entry.path().strip_prefix(from)?);
+        routes.push(ExportRoute {
+            url: String::new(),
+            file,
+            deps: vec![Dependency::Asset(entry.path().to_path_buf())],
+        });
+    }
+
+    Ok(routes)
+}
+
+/// Delete a stale output file and any directories it leaves empty
+fn remove_output(out: &Path, file: &Path) -> anyhow::Result<()> {
+    let path = out.join(file);
+    match std::fs::remove_file(&path) {
+        Ok(()) => tracing::debug!("Removed stale {:?}", path),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e).with_context(|| format!("Failed to remove {:?}", path)),
+    }
+
+    // `remove_dir` refuses non-empty directories, which is where to stop
+    let mut dir = path.parent();
+    while let Some(parent) = dir.filter(|d| *d != out) {
+        if std::fs::remove_dir(parent).is_err() {
+            break;
+        }
+        dir = parent.parent();
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use manifest::MANIFEST_FILE;
+
+    #[test]
+    fn test_route_files() {
+        assert_eq!(ExportRoute::page(&[]).url, "/");
+        assert_eq!(ExportRoute::page(&[]).file, PathBuf::from("index.html"));
+
+        let feed = ExportRoute::file(&["posts", "feed.xml"]);
+        assert_eq!(feed.url, "/posts/feed.xml");
+        assert_eq!(feed.file, PathBuf::from("posts/feed.xml"));
+
+        let tag = ExportRoute::page(&["tags", "c++", "page", "2"]);
+        assert_eq!(tag.url, "/tags/c%2B%2B/page/2");
+        assert_eq!(tag.file, PathBuf::from("tags/c++/page/2/index.html"));
+    }
+
+    #[test]
+    fn test_asset_routes() {
+        let from = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(from.path().join("css")).unwrap();
+        std::fs::write(from.path().join("css/site.css"), "body {}").unwrap();
+        std::fs::write(from.path().join("robots.txt"), "").unwrap();
+
+        let mut routes = asset_routes(from.path(), Path::new("static")).unwrap();
+        routes.sort_by(|a, b| a.file.cmp(&b.file));
+        assert_eq!(routes.len(), 2);
+        assert_eq!(routes[0].file, PathBuf::from("static/css/site.css"));
+        assert_eq!(
+            routes[0].deps,
+            vec![Dependency::Asset(from.path().join("css/site.css"))]
+        );
+        assert!(
+            asset_routes(&from.path().join("missing"), Path::new("static"))
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_remove_output_prunes_empty_dirs() {
+        let out = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(out.path().join("posts/old")).unwrap();
+        std::fs::create_dir_all(out.path().join("posts/kept")).unwrap();
+        std::fs::write(out.path().join("posts/old/index.html"), "").unwrap();
+        std::fs::write(out.path().join("posts/kept/index.html"), "").unwrap();
+
+        remove_output(out.path(), Path::new("posts/old/index.html")).unwrap();
+        assert!(!out.path().join("posts/old").exists());
+        assert!(out.path().join("posts/kept/index.html").exists());
+
+        // Already gone is fine
+        remove_output(out.path(), Path::new("posts/old/index.html")).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_card_template_change_rewrites_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let repo_templates = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../templates");
+        for entry in walkdir::WalkDir::new(&repo_templates) {
+            let entry = entry.unwrap();
+            let to = root
+                .join("templates")
+                .join(entry.path().strip_prefix(&repo_templates).unwrap());
+            if entry.file_type().is_dir() {
+                std::fs::create_dir_all(to).unwrap();
+            } else {
+                std::fs::copy(entry.path(), to).unwrap();
+            }
+        }
+        std::fs::create_dir_all(root.join("content/posts")).unwrap();
+        std::fs::write(
+            root.join("content/authors.yaml"),
+            "- id: claude\n  name: Claude\n  card_template: partials/post_card_claude.html\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("content/posts/hello.md"),
+            "---\ntitle: Hello\nslug: hello\ndate: 2025-01-01\nauthor: claude\nfeatured_image: /images/hello.png\n---\n\nHello.\n",
+        )
+        .unwrap();
+
+        let config = Config {
+            content_path: root.join("content"),
+            templates_path: root.join("templates"),
+            static_path: root.join("static"),
+            export_cache: root.join("cache"),
+            ..Config::default()
+        };
+        let out = root.join("dist");
+        crate::build(config.clone(), &out).await.unwrap();
+        assert!(LEGACY_FILES.iter().all(|file| !out.join(file).exists()));
+        assert!(cache_dir(&config, &out).join(MANIFEST_FILE).exists());
+
+        let card = root.join("templates/partials/post_card_claude.html");
+        let source = std::fs::read_to_string(&card).unwrap();
+        std::fs::write(&card, format!("{}<!-- edited card -->", source)).unwrap();
+        crate::build(config, &out).await.unwrap();
+
+        let index = std::fs::read_to_string(out.join("index.html")).unwrap();
+        assert!(index.contains("<!-- edited card -->"));
+    }
+}
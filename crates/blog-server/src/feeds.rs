@@ -0,0 +1,575 @@
+//! Syndication feed generation (RSS 2.0, Atom 1.0 and JSON Feed 1.1)
+
+use std::borrow::Cow;
+
+use blog_content::{Author, Post, ReadingStats, RenderCache, Registry};
+use chrono::{NaiveDateTime, NaiveTime};
+use serde::Serialize;
+
+use crate::config::Config;
+use crate::routes::posts::{encode_query_value, ListQuery};
+
+/// Pre-generated site-wide feeds, rebuilt whenever the post cache is swapped
+#[derive(Debug, Clone, Default)]
+pub struct FeedCache {
+    pub rss: String,
+    pub atom: String,
+    pub json: String,
+}
+
+impl FeedCache {
+    /// Generate every unfiltered feed from the given posts
+    pub fn build(
+        posts: &[Post],
+        registry: &Registry,
+        config: &Config,
+        rendered: &RenderCache,
+    ) -> Self {
+        let query = ListQuery::default();
+        let rss = Feed::select(posts, &query, "/rss.xml", registry, config);
+        let atom = Feed::select(posts, &query, "/atom.xml", registry, config);
+        let json = Feed::select(posts, &query, "/feed.json", registry, config);
+
+        Self {
+            rss: build_rss(&rss, config, rendered),
+            atom: build_atom(&atom, config, rendered),
+            json: build_json_feed(&json, config, rendered),
+        }
+    }
+}
+
+/// A selection of posts to be written out in one of the feed formats
+pub struct Feed<'a> {
+    /// Feed title
+    pub title: String,
+    /// Path of the feed itself, including any filter query string
+    pub self_path: String,
+    /// Path of the HTML listing the feed mirrors
+    pub home_path: String,
+    /// Posts in the feed, newest first
+    pub posts: Vec<&'a Post>,
+}
+
+impl<'a> Feed<'a> {
+    /// Select the newest posts matching the listing filters
+    ///
+    /// Uses the same author/category filters as `/posts`. Drafts and posts
+    /// scheduled for later are always excluded, even when the server is
+    /// configured to show them.
+    pub fn select(
+        posts: &'a [Post],
+        query: &ListQuery,
+        path: &str,
+        registry: &Registry,
+        config: &Config,
+    ) -> Self {
+        let filter = query.filter_query_string();
+        let (title, self_path, home_path) = if filter.is_empty() {
+            (config.site_title.clone(), path.to_string(), "/".to_string())
+        } else {
+            (
+                format!("{} - {}", config.site_title, query.title(registry)),
+                format!("{}?{}", path, filter),
+                format!("/posts?{}", filter),
+            )
+        };
+
+        let now = config.now();
+        let posts = posts
+            .iter()
+            .filter(|p| !p.is_draft() && !p.is_scheduled_at(now) && query.matches(p))
+            .take(config.feed_items)
+            .collect();
+
+        Self {
+            title,
+            self_path,
+            home_path,
+            posts,
+        }
+    }
+}
+
+/// Summary and full body for a single feed entry
+struct EntryContent {
+    /// The frontmatter description, as plain text
+    description: Option<String>,
+    /// The post's opening as HTML, see [`Post::summary_html`]
+    summary_html: String,
+    /// The whole rendered post, when feeds carry full content
+    html: Option<String>,
+}
+
+impl EntryContent {
+    fn for_post(post: &Post, config: &Config, rendered: &RenderCache) -> Self {
+        Self {
+            description: post.frontmatter.description.clone(),
+            summary_html: post.summary_html.clone(),
+            html: config
+                .feed_full_content
+                .then(|| rendered.get_or_render(post).html.clone()),
+        }
+    }
+}
+
+/// Build an RSS 2.0 document
+pub fn build_rss(feed: &Feed, config: &Config, rendered: &RenderCache) -> String {
+    let base_url = config.base_url.trim_end_matches('/');
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<rss version=\"2.0\" xmlns:atom=\"http://www.w3.org/2005/Atom\" xmlns:dc=\"http://purl.org/dc/elements/1.1/\">\n");
+    xml.push_str("<channel>\n");
+    xml.push_str(&format!("<title>{}</title>\n", escape_xml(&feed.title)));
+    xml.push_str(&format!(
+        "<link>{}{}</link>\n",
+        escape_xml(base_url),
+        escape_xml(&feed.home_path)
+    ));
+    xml.push_str(&format!(
+        "<description>{}</description>\n",
+        escape_xml(&config.site_description)
+    ));
+    xml.push_str(&format!(
+        "<language>{}</language>\n",
+        escape_xml(&config.site_language)
+    ));
+    xml.push_str(&format!(
+        "<atom:link href=\"{}{}\" rel=\"self\" type=\"application/rss+xml\"/>\n",
+        escape_xml(base_url),
+        escape_xml(&feed.self_path)
+    ));
+    if let Some(newest) = feed.posts.first() {
+        xml.push_str(&format!(
+            "<lastBuildDate>{}</lastBuildDate>\n",
+            config.localize(newest.date()).to_rfc2822()
+        ));
+    }
+
+    for post in &feed.posts {
+        let link = config.absolute_url(&format!("/posts/{}", post.slug()));
+        let content = EntryContent::for_post(post, config, rendered);
+        // The full post, else the description, else the post's summary
+        let description = content
+            .html
+            .or(content.description)
+            .unwrap_or(content.summary_html);
+
+        xml.push_str("<item>\n");
+        xml.push_str(&format!("<title>{}</title>\n", escape_xml(post.title())));
+        xml.push_str(&format!("<link>{}</link>\n", escape_xml(&link)));
+        xml.push_str(&format!(
+            "<guid isPermaLink=\"true\">{}</guid>\n",
+            escape_xml(&link)
+        ));
+        xml.push_str(&format!(
+            "<pubDate>{}</pubDate>\n",
+            config.localize(post.date()).to_rfc2822()
+        ));
+        for author in &post.authors {
+            xml.push_str(&format!(
+                "<dc:creator>{}</dc:creator>\n",
+                escape_xml(&author.name)
+            ));
+        }
+        for category in categories(post) {
+            xml.push_str(&format!("<category>{}</category>\n", escape_xml(category)));
+        }
+        xml.push_str(&format!(
+            "<description>{}</description>\n",
+            escape_xml(&description)
+        ));
+        xml.push_str("</item>\n");
+    }
+
+    xml.push_str("</channel>\n");
+    xml.push_str("</rss>\n");
+
+    xml
+}
+
+/// Build an Atom 1.0 document
+pub fn build_atom(feed: &Feed, config: &Config, rendered: &RenderCache) -> String {
+    let base_url = config.base_url.trim_end_matches('/');
+    let feed_updated = feed
+        .posts
+        .iter()
+        .map(|p| updated(p))
+        .max()
+        .unwrap_or_default();
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!(
+        "<feed xmlns=\"http://www.w3.org/2005/Atom\" xml:lang=\"{}\">\n",
+        escape_xml(&config.site_language)
+    ));
+    xml.push_str(&format!("<title>{}</title>\n", escape_xml(&feed.title)));
+    xml.push_str(&format!(
+        "<subtitle>{}</subtitle>\n",
+        escape_xml(&config.site_description)
+    ));
+    xml.push_str(&format!(
+        "<id>{}{}</id>\n",
+        escape_xml(base_url),
+        escape_xml(&feed.self_path)
+    ));
+    xml.push_str(&format!(
+        "<link href=\"{}{}\" rel=\"self\" type=\"application/atom+xml\"/>\n",
+        escape_xml(base_url),
+        escape_xml(&feed.self_path)
+    ));
+    xml.push_str(&format!(
+        "<link href=\"{}{}\" rel=\"alternate\" type=\"text/html\"/>\n",
+        escape_xml(base_url),
+        escape_xml(&feed.home_path)
+    ));
+    xml.push_str(&format!("<updated>{}</updated>\n", rfc3339(feed_updated, config)));
+
+    for post in &feed.posts {
+        let link = config.absolute_url(&format!("/posts/{}", post.slug()));
+        let content = EntryContent::for_post(post, config, rendered);
+
+        xml.push_str("<entry>\n");
+        xml.push_str(&format!("<title>{}</title>\n", escape_xml(post.title())));
+        xml.push_str(&format!("<id>{}</id>\n", escape_xml(&link)));
+        xml.push_str(&format!(
+            "<link href=\"{}\" rel=\"alternate\" type=\"text/html\"/>\n",
+            escape_xml(&link)
+        ));
+        xml.push_str(&format!(
+            "<published>{}</published>\n",
+            rfc3339(post.date(), config)
+        ));
+        xml.push_str(&format!("<updated>{}</updated>\n", rfc3339(updated(post), config)));
+        for author in &post.authors {
+            match author_url(author, base_url) {
+                Some(url) => xml.push_str(&format!(
+                    "<author><name>{}</name><uri>{}</uri></author>\n",
+                    escape_xml(&author.name),
+                    escape_xml(&url)
+                )),
+                None => xml.push_str(&format!(
+                    "<author><name>{}</name></author>\n",
+                    escape_xml(&author.name)
+                )),
+            }
+        }
+        for category in categories(post) {
+            xml.push_str(&format!("<category term=\"{}\"/>\n", escape_xml(category)));
+        }
+        match content.description {
+            Some(ref description) => {
+                xml.push_str(&format!("<summary>{}</summary>\n", escape_xml(description)));
+            }
+            None => xml.push_str(&format!(
+                "<summary type=\"html\">{}</summary>\n",
+                escape_xml(&content.summary_html)
+            )),
+        }
+        if let Some(ref html) = content.html {
+            xml.push_str(&format!(
+                "<content type=\"html\">{}</content>\n",
+                escape_xml(html)
+            ));
+        }
+        xml.push_str("</entry>\n");
+    }
+
+    xml.push_str("</feed>\n");
+
+    xml
+}
+
+/// JSON Feed 1.1 top-level document
+#[derive(Serialize)]
+struct JsonFeed<'a> {
+    version: &'static str,
+    title: &'a str,
+    description: &'a str,
+    language: &'a str,
+    home_page_url: String,
+    feed_url: String,
+    items: Vec<JsonFeedItem<'a>>,
+}
+
+/// JSON Feed 1.1 item
+#[derive(Serialize)]
+struct JsonFeedItem<'a> {
+    id: String,
+    url: String,
+    title: &'a str,
+    content_html: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    summary: Option<String>,
+    date_published: String,
+    date_modified: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    authors: Vec<JsonFeedAuthor<'a>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<&'a str>,
+    /// Extension with the post's word count, reading time and code blocks
+    #[serde(rename = "_reading")]
+    reading: ReadingStats,
+}
+
+/// JSON Feed 1.1 author object
+#[derive(Serialize)]
+struct JsonFeedAuthor<'a> {
+    name: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    avatar: Option<&'a str>,
+}
+
+/// Build a JSON Feed 1.1 document
+pub fn build_json_feed(feed: &Feed, config: &Config, rendered: &RenderCache) -> String {
+    let base_url = config.base_url.trim_end_matches('/');
+
+    let items = feed
+        .posts
+        .iter()
+        .map(|post| {
+            let link = config.absolute_url(&format!("/posts/{}", post.slug()));
+            let content = EntryContent::for_post(post, config, rendered);
+
+            JsonFeedItem {
+                id: link.clone(),
+                url: link,
+                title: post.title(),
+                // JSON Feed requires content_html or content_text on every item
+                content_html: content.html.unwrap_or(content.summary_html),
+                summary: content.description,
+                date_published: rfc3339(post.date(), config),
+                date_modified: rfc3339(updated(post), config),
+                authors: post
+                    .authors
+                    .iter()
+                    .map(|author| JsonFeedAuthor {
+                        name: &author.name,
+                        url: author_url(author, base_url),
+                        avatar: author.avatar.as_deref(),
+                    })
+                    .collect(),
+                tags: categories(post).collect(),
+                reading: post.stats,
+            }
+        })
+        .collect();
+
+    let json_feed = JsonFeed {
+        version: "https://jsonfeed.org/version/1.1",
+        title: &feed.title,
+        description: &config.site_description,
+        language: &config.site_language,
+        home_page_url: format!("{}{}", base_url, feed.home_path),
+        feed_url: format!("{}{}", base_url, feed.self_path),
+        items,
+    };
+
+    serde_json::to_string_pretty(&json_feed).unwrap_or_default()
+}
+
+/// Absolute profile URL for authors that have a profile page
+fn author_url(author: &Author, base_url: &str) -> Option<String> {
+    author
+        .registered
+        .then(|| format!("{}/authors/{}", base_url, encode_query_value(&author.id)))
+}
+
+/// Category followed by tags, used for feed categories
+fn categories(post: &Post) -> impl Iterator<Item = &str> {
+    post.frontmatter
+        .category
+        .iter()
+        .chain(post.frontmatter.tags.iter())
+        .map(String::as_str)
+}
+
+/// Last modification time: `updated` when set, otherwise the publish date
+pub(crate) fn updated(post: &Post) -> NaiveDateTime {
+    post.frontmatter
+        .updated
+        .map(|d| d.and_time(NaiveTime::MIN))
+        .unwrap_or_else(|| post.date())
+        .max(post.date())
+}
+
+/// Format a post datetime as RFC 3339, in the site's timezone
+pub(crate) fn rfc3339(dt: NaiveDateTime, config: &Config) -> String {
+    config.localize(dt).to_rfc3339()
+}
+
+/// Escape text for use in XML element content and attribute values
+pub fn escape_xml(text: &str) -> Cow<'_, str> {
+    if !text.contains(['&', '<', '>', '"', '\'']) {
+        return Cow::Borrowed(text);
+    }
+
+    let mut escaped = String::with_capacity(text.len() + 16);
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+
+    Cow::Owned(escaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use blog_content::Frontmatter;
+
+    fn test_post(slug: &str, date: &str, author: &str, draft: bool) -> Post {
+        let yaml = format!(
+            "title: \"Fish & <Chips>\"\nslug: \"{}\"\ndate: {}\nauthor: \"{}\"\ndescription: \"A \\\"quoted\\\" summary\"\ntags: [\"rust\"]\ndraft: {}\n",
+            slug, date, author, draft
+        );
+        let frontmatter: Frontmatter = serde_yaml::from_str(&yaml).unwrap();
+        Post::new(frontmatter, "Hello".to_string(), format!("{}.md", slug))
+    }
+
+    fn test_render_cache() -> RenderCache {
+        RenderCache::new(std::sync::Arc::new(blog_content::Renderer::default()))
+    }
+
+    fn test_config(feed_items: usize) -> Config {
+        Config {
+            base_url: "https://example.com".to_string(),
+            feed_items,
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn test_escape_xml() {
+        assert_eq!(escape_xml("plain"), "plain");
+        assert_eq!(
+            escape_xml("<a href=\"x\">Tom & Jerry's</a>"),
+            "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&apos;s&lt;/a&gt;"
+        );
+    }
+
+    #[test]
+    fn test_rss_excludes_unpublished_and_limits_items() {
+        let posts = vec![
+            test_post("scheduled", "2999-01-01", "Claude", false),
+            test_post("draft", "2025-01-03", "Claude", true),
+            test_post("second", "2025-01-02", "Claude", false),
+            test_post("first", "2025-01-01", "Claude", false),
+        ];
+        let config = test_config(1);
+
+        let feed = Feed::select(&posts, &ListQuery::default(), "/rss.xml", &Registry::default(), &config);
+        let xml = build_rss(&feed, &config, &test_render_cache());
+        assert!(!xml.contains("/posts/draft"));
+        assert!(!xml.contains("/posts/scheduled"));
+        assert!(xml.contains("<link>https://example.com/posts/second</link>"));
+        assert!(!xml.contains("/posts/first"));
+        assert!(xml.contains("<title>Fish &amp; &lt;Chips&gt;</title>"));
+        assert!(xml.contains("A &quot;quoted&quot; summary"));
+        assert!(xml.contains("<pubDate>Thu, 2 Jan 2025 00:00:00 +0000</pubDate>"));
+        assert!(xml.contains("<language>en</language>"));
+
+        // Dates are read in the site's timezone
+        let config = Config {
+            timezone: chrono_tz::Europe::Berlin,
+            ..test_config(1)
+        };
+        let xml = build_rss(&feed, &config, &test_render_cache());
+        assert!(xml.contains("<pubDate>Thu, 2 Jan 2025 00:00:00 +0100</pubDate>"));
+    }
+
+    #[test]
+    fn test_filtered_atom_feed() {
+        let mut posts = vec![
+            test_post("by-claude", "2025-01-02", "Claude", false),
+            test_post("by-gudnuf", "2025-01-01", "gudnuf", false),
+        ];
+        posts[0].frontmatter.updated = chrono::NaiveDate::from_ymd_opt(2025, 2, 1);
+        let config = test_config(10);
+        let query = ListQuery {
+            author: Some("Claude".to_string()),
+            ..ListQuery::default()
+        };
+
+        let feed = Feed::select(&posts, &query, "/posts/feed.xml", &Registry::default(), &config);
+        let xml = build_atom(&feed, &config, &test_render_cache());
+        assert!(xml.contains("https://example.com/posts/feed.xml?author=Claude"));
+        assert!(xml.contains("https://example.com/posts/by-claude"));
+        assert!(!xml.contains("by-gudnuf"));
+        assert!(xml.contains("<updated>2025-02-01T00:00:00+00:00</updated>"));
+        assert!(xml.contains("<author><name>Claude</name></author>"));
+        assert!(xml.contains("<category term=\"rust\"/>"));
+        assert!(xml.contains("xml:lang=\"en\""));
+    }
+
+    #[test]
+    fn test_json_feed() {
+        let posts = vec![test_post("hello", "2025-01-02", "gudnuf", false)];
+        let config = test_config(10);
+
+        let feed = Feed::select(&posts, &ListQuery::default(), "/feed.json", &Registry::default(), &config);
+        let json: serde_json::Value =
+            serde_json::from_str(&build_json_feed(&feed, &config, &test_render_cache())).unwrap();
+        assert_eq!(json["version"], "https://jsonfeed.org/version/1.1");
+        assert_eq!(json["feed_url"], "https://example.com/feed.json");
+        assert_eq!(json["language"], "en");
+        assert_eq!(json["items"][0]["url"], "https://example.com/posts/hello");
+        assert_eq!(json["items"][0]["authors"][0]["name"], "gudnuf");
+        assert_eq!(json["items"][0]["tags"][0], "rust");
+        assert_eq!(json["items"][0]["_reading"]["word_count"], 1);
+        assert_eq!(json["items"][0]["_reading"]["reading_time"], 1);
+    }
+
+    #[test]
+    fn test_summary_without_description() {
+        let mut post = test_post("hello", "2025-01-02", "Claude", false);
+        post.frontmatter.description = None;
+        let posts = vec![post];
+        let config = test_config(10);
+        let feed = Feed::select(&posts, &ListQuery::default(), "/atom.xml", &Registry::default(), &config);
+
+        let atom = build_atom(&feed, &config, &test_render_cache());
+        assert!(atom.contains("<summary type=\"html\">&lt;p&gt;Hello&lt;/p&gt;\n</summary>"));
+        assert!(!atom.contains("<content"));
+
+        let rss = build_rss(&feed, &config, &test_render_cache());
+        assert!(rss.contains("<description>&lt;p&gt;Hello&lt;/p&gt;\n</description>"));
+    }
+
+    #[test]
+    fn test_co_authored_post_lists_every_author() {
+        let mut post = test_post("duet", "2025-01-02", "Claude", false);
+        post.frontmatter.authors = vec!["claude".to_string(), "gudnuf".to_string()];
+        let mut posts = vec![post];
+        let registry = Registry {
+            authors: serde_yaml::from_str("- id: claude\n  name: Claude\n").unwrap(),
+            ..Registry::default()
+        };
+        registry.apply(&mut posts);
+        let config = test_config(10);
+
+        let query = ListQuery {
+            author: Some("gudnuf".to_string()),
+            ..ListQuery::default()
+        };
+        let feed = Feed::select(&posts, &query, "/posts/feed.xml", &registry, &config);
+        let rendered = test_render_cache();
+
+        let rss = build_rss(&feed, &config, &rendered);
+        assert!(rss.contains("<dc:creator>Claude</dc:creator>"));
+        assert!(rss.contains("<dc:creator>gudnuf</dc:creator>"));
+
+        let atom = build_atom(&feed, &config, &rendered);
+        assert!(atom.contains(
+            "<author><name>Claude</name><uri>https://example.com/authors/claude</uri></author>"
+        ));
+        assert!(atom.contains("<author><name>gudnuf</name></author>"));
+    }
+}
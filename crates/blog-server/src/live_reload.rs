@@ -0,0 +1,161 @@
+//! Browser live-reload for watch mode
+//!
+//! Each browser tab opens a Server-Sent Events connection that says what it is
+//! showing. When the watcher reloads content, only the tabs whose view is
+//! affected by the change are told to refresh.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use serde::Deserialize;
+use tokio::sync::broadcast;
+
+/// What a connected browser tab is currently showing
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct ClientView {
+    /// Slug of the post being viewed
+    pub post: Option<String>,
+    /// Slug of the static page being viewed
+    pub page: Option<String>,
+}
+
+impl ClientView {
+    /// Listings and the index show every post, so they react to any post change
+    fn is_listing(&self) -> bool {
+        self.post.is_none() && self.page.is_none()
+    }
+}
+
+/// A change that connected browsers may need to reload for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReloadEvent {
+    /// Templates changed; every page looks different
+    All,
+    /// These post slugs were added, edited or removed
+    Posts(Vec<String>),
+    /// These page slugs were added, edited or removed
+    Pages(Vec<String>),
+}
+
+impl ReloadEvent {
+    /// Whether a tab showing `view` needs to refresh for this change
+    pub fn affects(&self, view: &ClientView) -> bool {
+        match self {
+            ReloadEvent::All => true,
+            ReloadEvent::Posts(slugs) => {
+                view.is_listing() || view.post.as_ref().is_some_and(|slug| slugs.contains(slug))
+            }
+            ReloadEvent::Pages(slugs) => {
+                view.page.as_ref().is_some_and(|slug| slugs.contains(slug))
+            }
+        }
+    }
+}
+
+/// Registry of connected browsers and the channel used to notify them
+pub struct LiveReload {
+    sender: broadcast::Sender<ReloadEvent>,
+    clients: Mutex<HashMap<u64, ClientView>>,
+    next_id: AtomicU64,
+}
+
+impl Default for LiveReload {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(16);
+        Self {
+            sender,
+            clients: Mutex::new(HashMap::new()),
+            next_id: AtomicU64::new(0),
+        }
+    }
+}
+
+impl LiveReload {
+    /// Register a browser tab; it is unregistered when the guard is dropped
+    pub fn connect(self: &Arc<Self>, view: ClientView) -> ClientGuard {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        tracing::debug!("Live-reload client {} connected: {:?}", id, view);
+        self.clients.lock().insert(id, view.clone());
+
+        ClientGuard {
+            id,
+            view,
+            receiver: self.sender.subscribe(),
+            live_reload: Arc::clone(self),
+        }
+    }
+
+    /// Tell every affected browser tab to refresh
+    pub fn notify(&self, event: ReloadEvent) {
+        let affected = self
+            .clients
+            .lock()
+            .values()
+            .filter(|view| event.affects(view))
+            .count();
+
+        if affected > 0 {
+            tracing::info!("Live-reloading {} browser tab(s) for {:?}", affected, event);
+            // Sending only fails when nobody is subscribed
+            let _ = self.sender.send(event);
+        }
+    }
+}
+
+/// A connected browser tab, holding its subscription to reload events
+pub struct ClientGuard {
+    id: u64,
+    pub view: ClientView,
+    pub receiver: broadcast::Receiver<ReloadEvent>,
+    live_reload: Arc<LiveReload>,
+}
+
+impl Drop for ClientGuard {
+    fn drop(&mut self) {
+        tracing::debug!("Live-reload client {} disconnected", self.id);
+        self.live_reload.clients.lock().remove(&self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn post_view(slug: &str) -> ClientView {
+        ClientView {
+            post: Some(slug.to_string()),
+            page: None,
+        }
+    }
+
+    #[test]
+    fn test_post_change_only_reloads_that_post_and_listings() {
+        let event = ReloadEvent::Posts(vec!["edited".to_string()]);
+
+        assert!(event.affects(&post_view("edited")));
+        assert!(!event.affects(&post_view("other")));
+        assert!(event.affects(&ClientView::default()));
+        assert!(!event.affects(&ClientView {
+            post: None,
+            page: Some("about".to_string()),
+        }));
+    }
+
+    #[test]
+    fn test_template_change_reloads_everything() {
+        assert!(ReloadEvent::All.affects(&post_view("any")));
+        assert!(ReloadEvent::All.affects(&ClientView::default()));
+    }
+
+    #[test]
+    fn test_clients_unregister_on_drop() {
+        let live_reload = Arc::new(LiveReload::default());
+        let guard = live_reload.connect(post_view("hello"));
+        assert_eq!(live_reload.clients.lock().len(), 1);
+
+        drop(guard);
+        assert!(live_reload.clients.lock().is_empty());
+    }
+}
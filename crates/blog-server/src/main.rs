@@ -1,8 +1,18 @@
 //! Blog server - SSR blog with Axum
 
+mod cli;
 mod config;
+mod export;
+mod feeds;
+mod live_reload;
+mod og_image;
 mod routes;
+mod scheduler;
+mod search;
+mod seo;
+mod sitemap;
 mod templates;
+mod watcher;
 
 use std::net::SocketAddr;
 use std::path::Path;
@@ -19,16 +29,54 @@ use tower_http::{
 };
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+use crate::cli::{Cli, Command};
 use crate::config::Config;
+use crate::feeds::FeedCache;
+use crate::live_reload::LiveReload;
+use crate::og_image::OgImages;
+use crate::search::SearchIndexJson;
 use crate::templates::Templates;
-use blog_content::Post;
-use parking_lot::RwLock;
+use blog_content::{
+    Post, Registry, RenderCache, RenderOptions, RenderSnapshot, Renderer, SearchIndex, SeriesIndex, Timeline,
+};
+use parking_lot::{Mutex, RwLock};
 
 /// Application state shared across handlers
 pub struct AppState {
     pub config: Config,
     pub templates: Templates,
-    pub post_cache: Arc<RwLock<Vec<Post>>>,
+    pub renderer: Arc<Renderer>,
+    content: RwLock<Arc<ContentCache>>,
+    pub live_reload: Arc<LiveReload>,
+    pub og_images: OgImages,
+    /// Held by whoever is rebuilding the post cache, from reading the current
+    /// posts until the new content is swapped in, so reloads never overlap
+    pub reload_lock: Mutex<()>,
+}
+
+impl AppState {
+    /// The content loaded by the latest reload
+    ///
+    /// Everything in it was built from the same posts; a reload swaps in a
+    /// new snapshot without changing ones already handed out.
+    pub fn content(&self) -> Arc<ContentCache> {
+        self.content.read().clone()
+    }
+}
+
+/// Loaded posts and everything derived from them
+pub struct ContentCache {
+    pub posts: Vec<Post>,
+    /// Posts held back until their publish time, oldest first
+    pub scheduled: Vec<Post>,
+    pub registry: Registry,
+    pub series: SeriesIndex,
+    pub timeline: Timeline,
+    pub search_index: SearchIndex,
+    pub search_index_json: SearchIndexJson,
+    pub render_cache: RenderCache,
+    pub feeds: FeedCache,
+    pub sitemap: String,
 }
 
 /// Load all posts into memory cache
@@ -48,6 +96,88 @@ fn load_posts_into_cache(
     Ok(posts)
 }
 
+/// Swap in a freshly loaded set of posts and regenerate rendered HTML, feeds,
+/// series, previous/next links, the search index and the sitemap
+///
+/// Only posts whose content hash changed since the last load are re-rendered.
+/// Categories and author profiles are re-read too; if they fail to parse, the
+/// previous definitions are kept. Posts whose publish time has not arrived are
+/// set aside in `scheduled`, unless drafts are shown. Cached preview images
+/// that no longer match a post are deleted.
+///
+/// Callers that may run alongside another reload hold `reload_lock` from
+/// reading the posts they pass in until this returns.
+fn replace_post_cache(state: &AppState, posts: Vec<Post>) {
+    let previous = state.content();
+    let mut registry = match Registry::load(&state.config.content_path) {
+        Ok(registry) => registry,
+        Err(e) => {
+            tracing::error!("Failed to load categories or authors, keeping previous: {}", e);
+            previous.registry.clone()
+        }
+    };
+
+    let now = state.config.now();
+    let (mut scheduled, mut posts): (Vec<_>, Vec<_>) = posts
+        .into_iter()
+        .partition(|p| !state.config.enable_drafts && p.is_scheduled_at(now));
+    scheduled.sort_by_key(|p| p.publish_time());
+    if !scheduled.is_empty() {
+        tracing::info!("{} post(s) scheduled for later", scheduled.len());
+    }
+
+    // Posts without a byline are credited to the site's default author
+    registry.default_author =
+        Some(state.config.site_author.clone()).filter(|author| !author.is_empty());
+    registry.apply(&mut posts);
+
+    let mut render_cache = previous.render_cache.clone();
+    let stats = render_cache.refresh(&posts);
+    tracing::info!(
+        "Rendered {} posts ({} unchanged, {} removed)",
+        stats.rendered,
+        stats.reused,
+        stats.removed
+    );
+
+    let pages = blog_content::load_all_pages(&state.config.content_path).unwrap_or_else(|e| {
+        tracing::warn!("Failed to load pages for sitemap: {}", e);
+        Vec::new()
+    });
+
+    let series = SeriesIndex::build(&posts);
+    let timeline = Timeline::build(&posts);
+    let search_index = SearchIndex::build(&posts);
+    let search_index_json = SearchIndexJson::build(&posts, now);
+    let feeds = FeedCache::build(&posts, &registry, &state.config, &render_cache);
+    let sitemap = sitemap::build_sitemap(&posts, &pages, &registry, &state.config);
+    state.og_images.prune(&posts, &registry, &state.config.site_title);
+
+    // The navigation lists every author with a profile
+    state.templates.insert_global("site_authors", &registry.authors);
+    *state.content.write() = Arc::new(ContentCache {
+        posts,
+        scheduled,
+        registry,
+        series,
+        timeline,
+        search_index,
+        search_index_json,
+        render_cache,
+        feeds,
+        sitemap,
+    });
+}
+
+/// Every loaded post, whether published or scheduled, newest first
+fn all_posts(state: &AppState) -> Vec<Post> {
+    let content = state.content();
+    let mut posts = content.posts.clone();
+    posts.extend(content.scheduled.iter().cloned());
+    posts.sort_by_key(|p| std::cmp::Reverse(p.date()));
+    posts
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     // Initialize tracing
@@ -59,38 +189,78 @@ async fn main() -> anyhow::Result<()> {
         .with(tracing_subscriber::fmt::layer())
         .init();
 
+    let cli = Cli::parse(std::env::args().skip(1))?;
+    if cli.help {
+        println!("{}", cli::USAGE);
+        return Ok(());
+    }
+
     // Load configuration
-    let config = Config::load()?;
+    let mut config = Config::load(cli.config_file.as_deref(), &cli.overrides)?;
+    if cli.print_config {
+        print!("{}", config.to_toml()?);
+        return Ok(());
+    }
+    config.validate()?;
     tracing::info!("Configuration loaded: {:?}", config);
 
+    match cli.command {
+        Command::Serve => serve(config).await,
+        Command::Build { out } => {
+            // Nothing to reload in a one-off build
+            config.watch = false;
+            build(config, &out).await
+        }
+    }
+}
+
+/// Render the site to static files under `out`
+async fn build(config: Config, out: &Path) -> anyhow::Result<()> {
+    let templates = Templates::new(&config.templates_path, config.timezone)?;
+    templates.insert_global("site", &config.site());
+    templates.insert_global("live_reload", &false);
+    // There is no server to answer `/search?q=`, so search in the browser
+    templates.insert_global("client_search", &true);
+    // "3 days ago" would go stale in files that outlive the build
+    templates.insert_global("relative_dates", &false);
+
+    // Reuse highlighted post HTML from the last export into the same place
+    let render_cache = export::load_render_cache(&config, out);
+    let state = build_state(config, templates, render_cache)?;
+    let router = build_router(&state.config).with_state(state.clone());
+    export::export(&state, router, out).await?;
+
+    tracing::info!("Site exported to {:?}", out);
+    Ok(())
+}
+
+/// Run the HTTP server until a shutdown signal arrives
+async fn serve(config: Config) -> anyhow::Result<()> {
     // Initialize templates
-    let templates = Templates::new(&config.templates_path)?;
+    let templates = Templates::new(&config.templates_path, config.timezone)?;
+    templates.insert_global("site", &config.site());
+    templates.insert_global("live_reload", &config.watch);
+    templates.insert_global("relative_dates", &true);
     tracing::info!("Templates loaded from {:?}", config.templates_path);
 
-    // Initialize post cache
-    let initial_posts = load_posts_into_cache(&config.content_path, config.enable_drafts)?;
-    let post_cache = Arc::new(RwLock::new(initial_posts));
+    let state = build_state(config.clone(), templates, None)?;
+    let mut app = build_router(&config);
 
-    // Create shared state
-    let state = Arc::new(AppState {
-        config: config.clone(),
-        templates,
-        post_cache,
-    });
+    // Watch content and templates for changes when enabled, and let browsers
+    // subscribe to reload notifications
+    if config.watch {
+        watcher::spawn_watcher(state.clone())?;
+        app = app.route("/__livereload", get(routes::live_reload::events));
+    }
 
-    // Build router
-    let app = Router::new()
-        .route("/", get(routes::index::index))
-        .route("/health", get(routes::health))
-        .route("/posts", get(routes::posts::list))
-        .route("/posts/:slug", get(routes::posts::show))
-        .route("/pages/:slug", get(routes::pages::show))
-        .nest_service("/static", ServeDir::new(&config.static_path))
-        .nest_service("/images", ServeDir::new(config.content_path.join("images")))
+    let app = app
         .layer(CompressionLayer::new())
         .layer(TraceLayer::new_for_http())
         .with_state(state.clone());
 
+    // Publish scheduled posts as they come due
+    scheduler::spawn_scheduler(state.clone());
+
     // Spawn SIGHUP handler for cache reload
     spawn_sighup_handler(state);
 
@@ -108,6 +278,87 @@ async fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Load content and set up the state shared by every handler
+///
+/// Renderings in `render_cache` are reused for posts that have not changed.
+fn build_state(
+    config: Config,
+    templates: Templates,
+    render_cache: Option<RenderSnapshot>,
+) -> anyhow::Result<Arc<AppState>> {
+    // Initialize post cache
+    let initial_posts = load_posts_into_cache(&config.content_path, config.enable_drafts)?;
+
+    // Create shared state
+    let renderer = Arc::new(Renderer::new(RenderOptions {
+        highlight_code: config.highlight_code,
+        heading_anchors: config.heading_anchors,
+        ..RenderOptions::default()
+    }));
+    let mut cache = RenderCache::new(renderer.clone());
+    if let Some(snapshot) = render_cache {
+        let restored = cache.restore(snapshot);
+        tracing::debug!("Restored {} cached post renderings", restored);
+    }
+    let og_images = OgImages::new(&config.og_image_cache, &config.og_image_fonts);
+    let state = Arc::new(AppState {
+        config,
+        templates,
+        renderer: renderer.clone(),
+        content: RwLock::new(Arc::new(ContentCache {
+            posts: Vec::new(),
+            scheduled: Vec::new(),
+            registry: Registry::default(),
+            series: SeriesIndex::default(),
+            timeline: Timeline::default(),
+            search_index: SearchIndex::default(),
+            search_index_json: SearchIndexJson::default(),
+            render_cache: cache,
+            feeds: FeedCache::default(),
+            sitemap: String::new(),
+        })),
+        live_reload: Arc::new(LiveReload::default()),
+        og_images,
+        reload_lock: Mutex::new(()),
+    });
+    replace_post_cache(&state, initial_posts);
+
+    Ok(state)
+}
+
+/// Every page, feed and asset route, shared by the server and static export
+fn build_router(config: &Config) -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", get(routes::index::index))
+        .route("/health", get(routes::health))
+        .route("/rss.xml", get(routes::feeds::rss))
+        .route("/atom.xml", get(routes::feeds::atom))
+        .route("/feed.json", get(routes::feeds::json_feed))
+        .route("/sitemap.xml", get(routes::sitemap::sitemap))
+        .route("/robots.txt", get(routes::sitemap::robots))
+        .route("/posts", get(routes::posts::list))
+        .route("/posts/feed.xml", get(routes::feeds::posts_atom))
+        .route("/posts/rss.xml", get(routes::feeds::posts_rss))
+        .route("/posts/feed.json", get(routes::feeds::posts_json_feed))
+        .route("/posts/page/:page", get(routes::posts::list_page))
+        .route("/posts/:slug", get(routes::posts::show))
+        .route("/posts/:slug/og.png", get(routes::posts::og_image))
+        .route("/pages/:slug", get(routes::pages::show))
+        .route("/authors", get(routes::authors::index))
+        .route("/authors/:id", get(routes::authors::show))
+        .route("/authors/:id/page/:page", get(routes::authors::show_page))
+        .route("/categories/:slug", get(routes::categories::show))
+        .route("/categories/:slug/page/:page", get(routes::categories::show_page))
+        .route("/tags", get(routes::tags::index))
+        .route("/tags/:tag", get(routes::tags::show))
+        .route("/tags/:tag/page/:page", get(routes::tags::show_page))
+        .route("/series/:slug", get(routes::series::show))
+        .route("/search", get(routes::search::search))
+        .route("/search-index.json", get(routes::search::index_json))
+        .nest_service("/static", ServeDir::new(&config.static_path))
+        .nest_service("/images", ServeDir::new(config.content_path.join("images")))
+}
+
 async fn shutdown_signal() {
     let ctrl_c = async {
         tokio::signal::ctrl_c()
@@ -155,9 +406,10 @@ fn spawn_sighup_handler(state: Arc<AppState>) {
                 sighup.recv().await;
                 tracing::info!("SIGHUP received, reloading post cache");
 
+                let _reload = state.reload_lock.lock();
                 match load_posts_into_cache(&state.config.content_path, state.config.enable_drafts) {
                     Ok(new_posts) => {
-                        *state.post_cache.write() = new_posts;
+                        replace_post_cache(&state, new_posts);
                         tracing::info!("Post cache reloaded successfully");
                     }
                     Err(e) => {
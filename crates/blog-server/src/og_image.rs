@@ -0,0 +1,411 @@
+//! Generated social preview images
+//!
+//! Posts without a `featured_image` get a 1200×630 card at
+//! `/posts/:slug/og.png`: the title, author badge, date and category drawn
+//! from an SVG template in the author's colors and rasterized with resvg.
+//! Rendered images are cached on disk, keyed by a hash of everything drawn,
+//! so each one is only rasterized once per change to its post. Images no
+//! current post would be drawn as are deleted on every reload.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::Context as _;
+use blog_content::cache::ContentHasher;
+use blog_content::{Post, Registry};
+use resvg::{tiny_skia, usvg};
+
+const WIDTH: u32 = 1200;
+const HEIGHT: u32 = 630;
+
+/// Bumped whenever the template changes, so cached images are redrawn
+const LAYOUT_VERSION: u32 = 1;
+
+/// Accent for authors without a color of their own, the site's violet
+const DEFAULT_ACCENT: &str = "#a78bfa";
+
+/// Left margin of the card's text
+const MARGIN: u32 = 80;
+
+/// Title sizes to try, largest first: font size, characters per line and
+/// lines available
+const TITLE_SIZES: [(u32, usize, usize); 3] = [(64, 26, 3), (52, 32, 4), (44, 38, 5)];
+
+/// Renders preview images and keeps them in a cache directory
+pub struct OgImages {
+    cache_dir: PathBuf,
+    fontdb: Arc<usvg::fontdb::Database>,
+}
+
+impl OgImages {
+    /// Load the system fonts, plus any in `fonts_dir` if it exists
+    pub fn new(cache_dir: &Path, fonts_dir: &Path) -> Self {
+        let mut fontdb = usvg::fontdb::Database::new();
+        fontdb.load_system_fonts();
+        if fonts_dir.is_dir() {
+            fontdb.load_fonts_dir(fonts_dir);
+        }
+        if fontdb.is_empty() {
+            tracing::warn!("No fonts found, preview images will have no text");
+        }
+
+        Self {
+            cache_dir: cache_dir.to_path_buf(),
+            fontdb: Arc::new(fontdb),
+        }
+    }
+
+    /// PNG preview image for `post`, from the cache if it was drawn before
+    pub fn render(
+        &self,
+        post: &Post,
+        registry: &Registry,
+        site_title: &str,
+    ) -> anyhow::Result<Vec<u8>> {
+        let card = Card::new(post, registry, site_title);
+        let path = self.cache_dir.join(file_name(&card, post));
+
+        if let Ok(png) = std::fs::read(&path) {
+            return Ok(png);
+        }
+
+        let png = rasterize(&card.svg(), &self.fontdb)
+            .with_context(|| format!("Failed to draw preview image for {:?}", post.slug()))?;
+
+        // Write and rename, so a concurrent request never reads half a file
+        std::fs::create_dir_all(&self.cache_dir)
+            .with_context(|| format!("Failed to create {:?}", self.cache_dir))?;
+        let partial = path.with_extension("png.tmp");
+        std::fs::write(&partial, &png)
+            .and_then(|()| std::fs::rename(&partial, &path))
+            .with_context(|| format!("Failed to write {:?}", path))?;
+
+        tracing::debug!("Rendered preview image for {:?}", post.slug());
+        Ok(png)
+    }
+
+    /// Delete cached images that none of `posts` would be drawn as, left
+    /// behind by earlier versions of posts or by posts that are gone
+    pub fn prune(&self, posts: &[Post], registry: &Registry, site_title: &str) {
+        let Ok(entries) = std::fs::read_dir(&self.cache_dir) else {
+            return;
+        };
+        let current: HashSet<_> = posts
+            .iter()
+            .filter(|p| p.frontmatter.featured_image.is_none())
+            .map(|p| file_name(&Card::new(p, registry, site_title), p))
+            .collect();
+
+        let mut removed = 0;
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if !name.ends_with(".png") || current.contains(&name) {
+                continue;
+            }
+            match std::fs::remove_file(entry.path()) {
+                Ok(()) => removed += 1,
+                Err(e) => tracing::warn!("Failed to remove {:?}: {}", entry.path(), e),
+            }
+        }
+        if removed > 0 {
+            tracing::debug!("Removed {} outdated preview image(s)", removed);
+        }
+    }
+}
+
+/// Cache file an image of `card` is stored under
+fn file_name(card: &Card, post: &Post) -> String {
+    format!("{:016x}.png", card.hash(post))
+}
+
+/// Everything drawn on one post's card
+#[derive(Debug, Clone, PartialEq)]
+struct Card {
+    site_title: String,
+    title: String,
+    /// Byline names, joined
+    author: String,
+    /// Letter shown in the author badge
+    initial: char,
+    accent: String,
+    date: String,
+    category: Option<(String, String)>,
+}
+
+impl Card {
+    fn new(post: &Post, registry: &Registry, site_title: &str) -> Self {
+        let author = post
+            .authors
+            .iter()
+            .map(|a| a.name.as_str())
+            .collect::<Vec<_>>()
+            .join(" & ");
+        let accent = post
+            .authors
+            .first()
+            .and_then(|a| a.color.clone())
+            .unwrap_or_else(|| DEFAULT_ACCENT.to_string());
+        let category = post.frontmatter.category.as_deref().map(|slug| {
+            let category = registry.categories.get(slug);
+            (
+                registry.categories.display_name(slug).to_string(),
+                category
+                    .and_then(|c| c.color.clone())
+                    .unwrap_or_else(|| accent.clone()),
+            )
+        });
+
+        Self {
+            site_title: site_title.to_string(),
+            title: post.title().to_string(),
+            initial: author
+                .chars()
+                .next()
+                .map_or('?', |c| c.to_ascii_uppercase()),
+            author,
+            accent,
+            date: post.date().format("%B %d, %Y").to_string(),
+            category,
+        }
+    }
+
+    /// Cache key: the frontmatter and bylines the card is drawn from, plus
+    /// the template version
+    fn hash(&self, post: &Post) -> u64 {
+        let mut hasher = ContentHasher::new();
+        hasher.write(&LAYOUT_VERSION.to_le_bytes());
+        hasher.write_str(&serde_json::to_string(&post.frontmatter).unwrap_or_default());
+        hasher.write_str(&format!("{:?}", self));
+        hasher.finish()
+    }
+
+    fn svg(&self) -> String {
+        let (size, chars, lines) = TITLE_SIZES
+            .iter()
+            .copied()
+            .find(|&(_, chars, lines)| wrap(&self.title, chars).len() <= lines)
+            .unwrap_or(TITLE_SIZES[TITLE_SIZES.len() - 1]);
+        let title_lines = truncate(wrap(&self.title, chars), lines, chars);
+
+        let line_height = size * 6 / 5;
+        let title = title_lines
+            .iter()
+            .enumerate()
+            .map(|(i, line)| {
+                format!(
+                    r#"<tspan x="{}" y="{}">{}</tspan>"#,
+                    MARGIN,
+                    190 + i as u32 * line_height,
+                    escape(line)
+                )
+            })
+            .collect::<String>();
+
+        let category = self
+            .category
+            .as_ref()
+            .map(|(name, color)| {
+                format!(
+                    r##"<tspan fill="#64748b">&#160;·&#160;</tspan><tspan fill="{}">{}</tspan>"##,
+                    escape(color),
+                    escape(name)
+                )
+            })
+            .unwrap_or_default();
+
+        format!(
+            r##"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">
+  <defs>
+    <radialGradient id="glow" cx="1" cy="0" r="1">
+      <stop offset="0" stop-color="{accent}" stop-opacity="0.35"/>
+      <stop offset="1" stop-color="{accent}" stop-opacity="0"/>
+    </radialGradient>
+  </defs>
+  <rect width="{w}" height="{h}" fill="#0f0d13"/>
+  <rect width="{w}" height="{h}" fill="url(#glow)"/>
+  <rect width="16" height="{h}" fill="{accent}"/>
+  <text x="{m}" y="110" font-family="Inter, 'DejaVu Sans', sans-serif" font-size="28" letter-spacing="2" fill="#94a3b8">{site}</text>
+  <text font-family="Fraunces, Georgia, 'DejaVu Serif', serif" font-weight="bold" font-size="{size}" fill="#f1f5f9">{title}</text>
+  <circle cx="{badge_x}" cy="530" r="32" fill="{accent}"/>
+  <text x="{badge_x}" y="541" text-anchor="middle" font-family="Inter, 'DejaVu Sans', sans-serif" font-weight="bold" font-size="30" fill="#0f0d13">{initial}</text>
+  <text x="{name_x}" y="541" font-family="Inter, 'DejaVu Sans', sans-serif" font-weight="bold" font-size="30" fill="#e2e8f0">{author}</text>
+  <text x="{right}" y="541" text-anchor="end" font-family="Inter, 'DejaVu Sans', sans-serif" font-size="26" fill="#64748b">{date}{category}</text>
+</svg>
+"##,
+            w = WIDTH,
+            h = HEIGHT,
+            m = MARGIN,
+            accent = escape(&self.accent),
+            site = escape(&self.site_title.to_uppercase()),
+            size = size,
+            title = title,
+            badge_x = MARGIN + 32,
+            name_x = MARGIN + 84,
+            initial = escape(&self.initial.to_string()),
+            author = escape(&self.author),
+            right = WIDTH - MARGIN,
+            date = escape(&self.date),
+            category = category,
+        )
+    }
+}
+
+fn rasterize(svg: &str, fontdb: &Arc<usvg::fontdb::Database>) -> anyhow::Result<Vec<u8>> {
+    let options = usvg::Options {
+        fontdb: fontdb.clone(),
+        ..usvg::Options::default()
+    };
+    let tree = usvg::Tree::from_str(svg, &options)?;
+
+    let mut pixmap =
+        tiny_skia::Pixmap::new(WIDTH, HEIGHT).context("Preview image size is invalid")?;
+    resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+    Ok(pixmap.encode_png()?)
+}
+
+/// Break `text` into lines of at most `width` characters at spaces
+///
+/// A single word longer than a line gets a line of its own.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+
+    for word in text.split_whitespace() {
+        match lines.last_mut() {
+            Some(line) if line.chars().count() + 1 + word.chars().count() <= width => {
+                line.push(' ');
+                line.push_str(word);
+            }
+            _ => lines.push(word.to_string()),
+        }
+    }
+
+    lines
+}
+
+/// Keep the first `max` lines, ending the last with an ellipsis if any were
+/// dropped or it is too long
+fn truncate(mut lines: Vec<String>, max: usize, width: usize) -> Vec<String> {
+    let cut = lines.len() > max;
+    lines.truncate(max);
+
+    if let Some(last) = lines.last_mut() {
+        if cut || last.chars().count() > width {
+            let mut shortened: String = last.chars().take(width - 1).collect();
+            shortened.truncate(shortened.trim_end().len());
+            shortened.push('…');
+            *last = shortened;
+        }
+    }
+
+    lines
+}
+
+/// Escape text for an SVG element or attribute
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use blog_content::Frontmatter;
+
+    fn test_post(title: &str) -> Post {
+        let yaml = format!(
+            "title: \"{}\"\nslug: hello\ndate: 2025-01-02\nauthor: claude\ncategory: devops\n",
+            title
+        );
+        let frontmatter: Frontmatter = serde_yaml::from_str(&yaml).unwrap();
+        let mut posts = vec![Post::new(
+            frontmatter,
+            String::new(),
+            "hello.md".to_string(),
+        )];
+        let registry = test_registry();
+        registry.apply(&mut posts);
+        posts.remove(0)
+    }
+
+    fn test_registry() -> Registry {
+        Registry {
+            authors: serde_yaml::from_str("- id: claude\n  name: Claude\n  color: \"#3b82f6\"\n")
+                .unwrap(),
+            categories: serde_yaml::from_str("- slug: devops\n  name: DevOps\n").unwrap(),
+            ..Registry::default()
+        }
+    }
+
+    #[test]
+    fn test_wrap_and_truncate() {
+        assert_eq!(
+            wrap("Building a blog   with Rust", 12),
+            vec!["Building a", "blog with", "Rust"]
+        );
+        assert_eq!(
+            wrap("Supercalifragilistic", 5),
+            vec!["Supercalifragilistic"]
+        );
+        assert_eq!(
+            truncate(wrap("one two three four", 7), 2, 7),
+            vec!["one two", "three…"]
+        );
+        assert_eq!(truncate(vec!["abcdefgh".to_string()], 1, 5), vec!["abcd…"]);
+    }
+
+    #[test]
+    fn test_card_uses_author_and_category() {
+        let card = Card::new(&test_post("Hello & <world>"), &test_registry(), "Site");
+        assert_eq!(card.author, "Claude");
+        assert_eq!(card.initial, 'C');
+        assert_eq!(card.accent, "#3b82f6");
+        assert_eq!(card.date, "January 02, 2025");
+        assert_eq!(
+            card.category,
+            Some(("DevOps".to_string(), "#3b82f6".to_string()))
+        );
+
+        let svg = card.svg();
+        assert!(svg.contains("Hello &amp; &lt;world&gt;"));
+        assert!(svg.contains(r##"fill="#3b82f6""##));
+    }
+
+    #[test]
+    fn test_render_is_cached() {
+        let cache = tempfile::tempdir().unwrap();
+        let images = OgImages::new(cache.path(), &cache.path().join("fonts"));
+        let post = test_post("Hello");
+
+        let png = images.render(&post, &test_registry(), "Site").unwrap();
+        assert!(png.starts_with(b"\x89PNG"));
+        assert_eq!(std::fs::read_dir(cache.path()).unwrap().count(), 1);
+
+        assert_eq!(images.render(&post, &test_registry(), "Site").unwrap(), png);
+        images
+            .render(&test_post("Changed"), &test_registry(), "Site")
+            .unwrap();
+        assert_eq!(std::fs::read_dir(cache.path()).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn test_prune_keeps_only_current_images() {
+        let cache = tempfile::tempdir().unwrap();
+        let images = OgImages::new(cache.path(), &cache.path().join("fonts"));
+        let old = test_post("Hello");
+        let new = test_post("Changed");
+        images.render(&old, &test_registry(), "Site").unwrap();
+        let png = images.render(&new, &test_registry(), "Site").unwrap();
+
+        images.prune(std::slice::from_ref(&new), &test_registry(), "Site");
+
+        let files: Vec<_> = std::fs::read_dir(cache.path())
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .collect();
+        assert_eq!(files.len(), 1);
+        assert_eq!(std::fs::read(&files[0]).unwrap(), png);
+    }
+}
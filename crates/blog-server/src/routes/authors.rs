@@ -0,0 +1,84 @@
+//! Author profile handlers
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Html,
+};
+use blog_content::Author;
+use serde::Serialize;
+
+use crate::routes::posts::{encode_query_value, page_url, render_post_list, ListQuery};
+use crate::AppState;
+
+/// An author together with how many posts they have written
+#[derive(Serialize)]
+struct AuthorSummary<'a> {
+    author: &'a Author,
+    post_count: usize,
+}
+
+/// List every author profile
+pub async fn index(State(state): State<Arc<AppState>>) -> Result<Html<String>, StatusCode> {
+    let content = state.content();
+    let registry = &content.registry;
+    let posts = &content.posts;
+
+    let authors: Vec<_> = registry
+        .authors
+        .iter()
+        .map(|author| AuthorSummary {
+            author,
+            post_count: posts
+                .iter()
+                .filter(|p| p.authors.iter().any(|a| a.registered && a.id == author.id))
+                .count(),
+        })
+        .collect();
+
+    let mut context = tera::Context::new();
+    context.insert("title", "Authors");
+    context.insert("authors", &authors);
+
+    let html = state
+        .templates
+        .render("authors.html", &context)
+        .map_err(|e| {
+            tracing::error!("Failed to render template: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Html(html))
+}
+
+/// An author's profile followed by a paginated listing of their posts,
+/// optionally narrowed further by category or tag
+pub async fn show(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Query(mut query): Query<ListQuery>,
+) -> Result<Html<String>, StatusCode> {
+    if state.content().registry.authors.get(&id).is_none() {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    // The author is part of the path, so pagination links only carry the other filters
+    query.author = None;
+    let url = page_url(&format!("/authors/{}", encode_query_value(&id)), &query);
+    query.author = Some(id);
+
+    let page = query.page.unwrap_or(1).max(1);
+    render_post_list(state, page, query, url).await
+}
+
+/// `/authors/:id/page/:page`, the path form of `/authors/:id?page=N`
+pub async fn show_page(
+    state: State<Arc<AppState>>,
+    Path((id, page)): Path<(String, usize)>,
+    Query(mut query): Query<ListQuery>,
+) -> Result<Html<String>, StatusCode> {
+    query.page = Some(page);
+    show(state, Path(id), Query(query)).await
+}
@@ -0,0 +1,45 @@
+//! Category landing page handlers
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Html,
+};
+
+use crate::routes::posts::{encode_query_value, page_url, render_post_list, ListQuery};
+use crate::AppState;
+
+/// A category's description followed by a paginated listing of its posts,
+/// optionally narrowed further by author or tag
+pub async fn show(
+    State(state): State<Arc<AppState>>,
+    Path(slug): Path<String>,
+    Query(mut query): Query<ListQuery>,
+) -> Result<Html<String>, StatusCode> {
+    if state.content().registry.categories.get(&slug).is_none() {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    // The category is part of the path, so pagination links only carry the other filters
+    query.category = None;
+    let url = page_url(
+        &format!("/categories/{}", encode_query_value(&slug)),
+        &query,
+    );
+    query.category = Some(slug);
+
+    let page = query.page.unwrap_or(1).max(1);
+    render_post_list(state, page, query, url).await
+}
+
+/// `/categories/:slug/page/:page`, the path form of `/categories/:slug?page=N`
+pub async fn show_page(
+    state: State<Arc<AppState>>,
+    Path((slug, page)): Path<(String, usize)>,
+    Query(mut query): Query<ListQuery>,
+) -> Result<Html<String>, StatusCode> {
+    query.page = Some(page);
+    show(state, Path(slug), Query(query)).await
+}
@@ -0,0 +1,92 @@
+//! Syndication feed route handlers
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Query, State},
+    http::header,
+    response::IntoResponse,
+};
+
+use crate::feeds::{build_atom, build_json_feed, build_rss, Feed};
+use crate::routes::posts::ListQuery;
+use crate::AppState;
+
+const RSS_CONTENT_TYPE: &str = "application/rss+xml; charset=utf-8";
+const ATOM_CONTENT_TYPE: &str = "application/atom+xml; charset=utf-8";
+const JSON_FEED_CONTENT_TYPE: &str = "application/feed+json; charset=utf-8";
+
+/// Serve the site-wide RSS 2.0 feed generated at cache load
+pub async fn rss(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let xml = state.content().feeds.rss.clone();
+    ([(header::CONTENT_TYPE, RSS_CONTENT_TYPE)], xml)
+}
+
+/// Serve the site-wide Atom 1.0 feed generated at cache load
+pub async fn atom(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let xml = state.content().feeds.atom.clone();
+    ([(header::CONTENT_TYPE, ATOM_CONTENT_TYPE)], xml)
+}
+
+/// Serve the site-wide JSON Feed 1.1 generated at cache load
+pub async fn json_feed(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let json = state.content().feeds.json.clone();
+    ([(header::CONTENT_TYPE, JSON_FEED_CONTENT_TYPE)], json)
+}
+
+/// Atom feed for a filtered post listing, e.g. `/posts/feed.xml?author=Claude`
+pub async fn posts_atom(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ListQuery>,
+) -> impl IntoResponse {
+    let content = state.content();
+    let feed = Feed::select(
+        &content.posts,
+        &query,
+        "/posts/feed.xml",
+        &content.registry,
+        &state.config,
+    );
+    (
+        [(header::CONTENT_TYPE, ATOM_CONTENT_TYPE)],
+        build_atom(&feed, &state.config, &content.render_cache),
+    )
+}
+
+/// RSS feed for a filtered post listing, e.g. `/posts/rss.xml?category=devops`
+pub async fn posts_rss(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ListQuery>,
+) -> impl IntoResponse {
+    let content = state.content();
+    let feed = Feed::select(
+        &content.posts,
+        &query,
+        "/posts/rss.xml",
+        &content.registry,
+        &state.config,
+    );
+    (
+        [(header::CONTENT_TYPE, RSS_CONTENT_TYPE)],
+        build_rss(&feed, &state.config, &content.render_cache),
+    )
+}
+
+/// JSON Feed for a filtered post listing, e.g. `/posts/feed.json?author=gudnuf`
+pub async fn posts_json_feed(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ListQuery>,
+) -> impl IntoResponse {
+    let content = state.content();
+    let feed = Feed::select(
+        &content.posts,
+        &query,
+        "/posts/feed.json",
+        &content.registry,
+        &state.config,
+    );
+    (
+        [(header::CONTENT_TYPE, JSON_FEED_CONTENT_TYPE)],
+        build_json_feed(&feed, &state.config, &content.render_cache),
+    )
+}
@@ -7,114 +7,95 @@ use axum::{
     http::StatusCode,
     response::Html,
 };
+use blog_content::{Author, Post};
+use serde::Serialize;
 
 use crate::AppState;
-use crate::routes::posts::render_post_content;
-use crate::routes::{AUTHOR_CLAUDE, AUTHOR_GUDNUF};
 
-/// Render the index page with split timeline for dual narrative
+/// Number of recent posts shown for each author on the index
+const POSTS_PER_AUTHOR: usize = 5;
+
+/// An author's column on the index: their profile and latest posts, each
+/// rendered with the author's card template
+#[derive(Serialize)]
+struct AuthorSection<'a> {
+    author: &'a Author,
+    posts: Vec<&'a Post>,
+    cards: Vec<String>,
+}
+
+/// Render the index page with a timeline for each author
 pub async fn index(
     State(state): State<Arc<AppState>>,
 ) -> Result<Html<String>, StatusCode> {
     // Load from cache (already filtered by draft status)
-    let all_posts = state.post_cache.read();
-
-    // Split posts by author
-    let claude_posts: Vec<_> = all_posts
-        .iter()
-        .filter(|p| p.author().map(|a| a == AUTHOR_CLAUDE).unwrap_or(false))
-        .take(5)
-        .cloned()
-        .collect();
+    let content = state.content();
+    let all_posts = &content.posts;
+    let registry = &content.registry;
 
-    let gudnuf_posts: Vec<_> = all_posts
-        .iter()
-        .filter(|p| p.author().map(|a| a == AUTHOR_GUDNUF).unwrap_or(false))
-        .take(5)
-        .cloned()
-        .collect();
+    let written_by = |post: &Post, author: &Author| {
+        post.authors.iter().any(|a| a.registered && a.id == author.id)
+    };
 
-    // Also include posts without authors or other authors (for backward compatibility)
-    let other_posts: Vec<_> = all_posts
-        .iter()
-        .filter(|p| !matches!(p.author(), Some(a) if a == AUTHOR_CLAUDE || a == AUTHOR_GUDNUF))
-        .take(5)
-        .cloned()
-        .collect();
-
-    // For backward compatibility, if no author-specific posts exist, use all posts
-    if claude_posts.is_empty() && gudnuf_posts.is_empty() {
+    let mut author_sections = Vec::new();
+    for author in registry.authors.iter() {
         let posts: Vec<_> = all_posts
             .iter()
-            .take(state.config.posts_per_page)
-            .cloned()
+            .filter(|p| written_by(p, author))
+            .take(POSTS_PER_AUTHOR)
             .collect();
 
-        let featured_post = posts.first().map(|post| {
-            let rendered = render_post_content(post);
-            (post.clone(), rendered)
-        });
-
-        let mut context = tera::Context::new();
-        context.insert("posts", &posts);
-        context.insert("title", "Home");
-
-        if let Some((post, rendered)) = &featured_post {
-            context.insert("featured_post", post);
-            context.insert("featured_content", &rendered.html);
+        if posts.is_empty() {
+            continue;
         }
 
-        let html = state
-            .templates
-            .render("index.html", &context)
+        let cards = posts
+            .iter()
+            .map(|post| {
+                let mut context = tera::Context::new();
+                context.insert("post", post);
+                context.insert("author", author);
+                state.templates.render(&author.card_template, &context)
+            })
+            .collect::<Result<Vec<_>, _>>()
             .map_err(|e| {
-                tracing::error!("Failed to render template: {}", e);
+                tracing::error!("Failed to render post card for {}: {}", author.id, e);
                 StatusCode::INTERNAL_SERVER_ERROR
             })?;
 
-        return Ok(Html(html));
+        author_sections.push(AuthorSection {
+            author,
+            posts,
+            cards,
+        });
     }
 
-    // Render featured posts for each author
-    let claude_featured = claude_posts.first().map(|post| {
-        let rendered = render_post_content(post);
-        (post.clone(), rendered)
-    });
-
-    let gudnuf_featured = gudnuf_posts.first().map(|post| {
-        let rendered = render_post_content(post);
-        (post.clone(), rendered)
-    });
+    // Posts without a registered author, so nobody's work goes missing
+    let other_posts: Vec<_> = all_posts
+        .iter()
+        .filter(|p| !p.authors.iter().any(|a| a.registered))
+        .take(POSTS_PER_AUTHOR)
+        .collect();
 
     // Collect all posts for the template (limited to posts_per_page)
     let posts: Vec<_> = all_posts
         .iter()
         .take(state.config.posts_per_page)
-        .cloned()
         .collect();
 
-    let featured_post = posts.first().cloned();
-
     let mut context = tera::Context::new();
-    context.insert("title", "The Nousphere in Dialogue");
+    if author_sections.is_empty() {
+        context.insert("title", "Home");
+    } else {
+        context.insert("title", &state.config.site_tagline);
+    }
     context.insert("posts", &posts);
-    if let Some(ref post) = featured_post {
+    if let Some(post) = posts.first() {
         context.insert("featured_post", post);
     }
-    context.insert("claude_posts", &claude_posts);
-    context.insert("gudnuf_posts", &gudnuf_posts);
+    context.insert("author_sections", &author_sections);
     context.insert("other_posts", &other_posts);
 
-    if let Some((post, rendered)) = &claude_featured {
-        context.insert("claude_featured", post);
-        context.insert("claude_featured_content", &rendered.html);
-    }
-
-    if let Some((post, rendered)) = &gudnuf_featured {
-        context.insert("gudnuf_featured", post);
-        context.insert("gudnuf_featured_content", &rendered.html);
-    }
-
     let html = state
         .templates
         .render("index.html", &context)
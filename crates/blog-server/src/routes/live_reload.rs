@@ -0,0 +1,41 @@
+//! Live-reload Server-Sent Events endpoint (watch mode only)
+
+use std::convert::Infallible;
+use std::sync::Arc;
+
+use axum::{
+    extract::{Query, State},
+    response::sse::{Event, KeepAlive, Sse},
+};
+use futures_util::stream::{self, Stream};
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::live_reload::ClientView;
+use crate::AppState;
+
+/// Stream `reload` events to a browser tab whenever its view is affected
+///
+/// The tab identifies what it is showing with `?post=<slug>` or
+/// `?page=<slug>`; with neither it is treated as a listing page.
+pub async fn events(
+    State(state): State<Arc<AppState>>,
+    Query(view): Query<ClientView>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let client = state.live_reload.connect(view);
+
+    let stream = stream::unfold(client, |mut client| async move {
+        loop {
+            match client.receiver.recv().await {
+                Ok(event) if event.affects(&client.view) => break,
+                Ok(_) => continue,
+                // Missed some events; reload to be safe
+                Err(RecvError::Lagged(_)) => break,
+                Err(RecvError::Closed) => return None,
+            }
+        }
+
+        Some((Ok(Event::default().event("reload").data("reload")), client))
+    });
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
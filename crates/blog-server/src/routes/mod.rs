@@ -1,15 +1,19 @@
 //! Route handlers
 
+pub mod authors;
+pub mod categories;
+pub mod feeds;
 pub mod index;
+pub mod live_reload;
 pub mod pages;
 pub mod posts;
+pub mod search;
+pub mod series;
+pub mod sitemap;
+pub mod tags;
 
 use axum::http::StatusCode;
 
-// Author constants for dual-narrative blog
-pub const AUTHOR_CLAUDE: &str = "Claude";
-pub const AUTHOR_GUDNUF: &str = "gudnuf";
-
 /// Health check endpoint
 pub async fn health() -> StatusCode {
     StatusCode::OK
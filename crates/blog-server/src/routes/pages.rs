@@ -8,8 +8,8 @@ use axum::{
     response::Html,
 };
 use blog_content::load_page;
-use pulldown_cmark::{Options, Parser};
 
+use crate::seo::SeoMeta;
 use crate::AppState;
 
 /// Show a static page
@@ -22,30 +22,31 @@ pub async fn show(
         return Err(StatusCode::BAD_REQUEST);
     }
 
-    let page = load_page(&slug, &state.config.content_path)
-        .map_err(|e| {
-            tracing::warn!("Page not found: {} - {}", slug, e);
-            StatusCode::NOT_FOUND
-        })?;
+    let page = load_page(&slug, &state.config.content_path).map_err(|e| {
+        tracing::warn!("Page not found: {} - {}", slug, e);
+        StatusCode::NOT_FOUND
+    })?;
 
-    // Render markdown
-    let options = Options::all();
-    let parser = Parser::new_ext(&page.raw_content, options);
-    let mut html_content = String::new();
-    pulldown_cmark::html::push_html(&mut html_content, parser);
+    // Render markdown through the shared pipeline (highlighting, anchors, TOC)
+    let rendered = state.renderer.render_page(&page);
 
     let mut context = tera::Context::new();
     context.insert("page", &page);
-    context.insert("content", &html_content);
+    context.insert("content", &rendered.html);
     context.insert("title", &page.title);
+    context.insert("seo", &SeoMeta::for_page(&page, &state.config));
 
-    let html = state
-        .templates
-        .render("page.html", &context)
-        .map_err(|e| {
-            tracing::error!("Failed to render template: {}", e);
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+    if let Some(ref toc_html) = rendered.toc {
+        context.insert("toc", toc_html);
+        context.insert("has_toc", &true);
+    } else {
+        context.insert("has_toc", &false);
+    }
+
+    let html = state.templates.render("page.html", &context).map_err(|e| {
+        tracing::error!("Failed to render template: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
 
     Ok(Html(html))
 }
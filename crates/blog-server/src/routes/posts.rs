@@ -4,24 +4,155 @@ use std::sync::Arc;
 
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
-    response::Html,
+    http::{header, StatusCode},
+    response::{Html, IntoResponse},
 };
-use blog_content::{
-    Post, RenderedContent,
-    highlighter::highlight_code,
-    toc::{extract_toc, render_toc},
-};
-use pulldown_cmark::{CodeBlockKind, CowStr, Event, Options, Parser, Tag, TagEnd};
+use blog_content::{Post, Registry, TimelineScope};
 use serde::{Deserialize, Serialize};
 
+use crate::seo::SeoMeta;
 use crate::AppState;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Default, Clone)]
 pub struct ListQuery {
     pub page: Option<usize>,
     pub author: Option<String>,
     pub category: Option<String>,
+    pub tag: Option<String>,
+}
+
+impl ListQuery {
+    /// Check whether a post passes the author, category and tag filters
+    pub fn matches(&self, post: &Post) -> bool {
+        let author_ok = self
+            .author
+            .as_ref()
+            .map(|filter| {
+                post.authors
+                    .iter()
+                    .any(|a| a.id == *filter || a.name == *filter)
+            })
+            .unwrap_or(true);
+
+        let category_ok = self
+            .category
+            .as_ref()
+            .map(|filter| post.frontmatter.category.as_ref() == Some(filter))
+            .unwrap_or(true);
+
+        let tag_ok = self
+            .tag
+            .as_ref()
+            .map(|filter| post.has_tag(filter))
+            .unwrap_or(true);
+
+        author_ok && category_ok && tag_ok
+    }
+
+    /// Heading for a listing with these filters applied
+    pub fn title(&self, registry: &Registry) -> String {
+        if let Some(ref a) = self.author {
+            let name = registry.authors.find(a).map(|author| author.name.as_str());
+            format!("{}'s Posts", name.unwrap_or(a))
+        } else if let Some(ref c) = self.category {
+            registry.categories.display_name(c).to_string()
+        } else if let Some(ref t) = self.tag {
+            format!("Posts tagged \u{201c}{}\u{201d}", blog_content::normalize_tag(t))
+        } else {
+            "All Posts".to_string()
+        }
+    }
+
+    /// Encode the author/category/tag filters as a query string, without the page
+    pub fn filter_query_string(&self) -> String {
+        let mut params = Vec::new();
+
+        if let Some(ref author) = self.author {
+            params.push(format!("author={}", encode_query_value(author)));
+        }
+        if let Some(ref category) = self.category {
+            params.push(format!("category={}", encode_query_value(category)));
+        }
+        if let Some(ref tag) = self.tag {
+            params.push(format!(
+                "tag={}",
+                encode_query_value(&blog_content::normalize_tag(tag))
+            ));
+        }
+
+        params.join("&")
+    }
+
+    /// Scope for previous/next links on posts opened from this listing
+    ///
+    /// Only author and category listings have one; the author wins when both
+    /// filters are set.
+    pub fn timeline_scope(&self, registry: &Registry) -> Option<TimelineScope> {
+        if let Some(ref a) = self.author {
+            let id = registry.authors.find(a).map(|author| author.id.as_str());
+            Some(TimelineScope::Author(id.unwrap_or(a).to_string()))
+        } else {
+            self.category
+                .as_ref()
+                .map(|c| TimelineScope::Category(c.clone()))
+        }
+    }
+
+    /// Listing that shows the posts in a timeline scope
+    fn for_scope(scope: &TimelineScope) -> Self {
+        match scope {
+            TimelineScope::All => Self::default(),
+            TimelineScope::Author(id) => Self {
+                author: Some(id.clone()),
+                ..Self::default()
+            },
+            TimelineScope::Category(slug) => Self {
+                category: Some(slug.clone()),
+                ..Self::default()
+            },
+        }
+    }
+}
+
+/// Query parameters accepted by a single post
+#[derive(Deserialize, Default)]
+pub struct ShowQuery {
+    /// Listing the reader arrived from, `author:<id>` or `category:<slug>`
+    pub from: Option<String>,
+}
+
+/// Parse a `from=` value into a timeline scope
+fn parse_scope(from: &str) -> Option<TimelineScope> {
+    match from.split_once(':')? {
+        ("author", id) if !id.is_empty() => Some(TimelineScope::Author(id.to_string())),
+        ("category", slug) if !slug.is_empty() => Some(TimelineScope::Category(slug.to_string())),
+        _ => None,
+    }
+}
+
+/// Query string that keeps post navigation within a scope, e.g.
+/// `?from=author%3Aclaude`, or an empty string for all posts
+fn scope_query_string(scope: &TimelineScope) -> String {
+    let from = match scope {
+        TimelineScope::All => return String::new(),
+        TimelineScope::Author(id) => format!("author:{}", id),
+        TimelineScope::Category(slug) => format!("category:{}", slug),
+    };
+    format!("?from={}", encode_query_value(&from))
+}
+
+/// Percent-encode a query string value or path segment
+pub(crate) fn encode_query_value(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
 }
 
 /// Data structure for related posts that can be serialized to Tera
@@ -37,44 +168,63 @@ pub async fn list(
     Query(query): Query<ListQuery>,
 ) -> Result<Html<String>, StatusCode> {
     let page = query.page.unwrap_or(1).max(1);
-    render_post_list(state, page, query.author, query.category).await
+    let page_url = page_url("/posts", &query);
+    render_post_list(state, page, query, page_url).await
 }
 
-async fn render_post_list(
-    state: Arc<AppState>,
-    page: usize,
-    author: Option<String>,
-    category: Option<String>,
+/// `/posts/page/:page`, the path form of `/posts?page=N`
+///
+/// Listings without extra filters link to this form so that static exports
+/// can store every page as a file.
+pub async fn list_page(
+    state: State<Arc<AppState>>,
+    Path(page): Path<usize>,
+    Query(mut query): Query<ListQuery>,
 ) -> Result<Html<String>, StatusCode> {
-    // Load from cache (already filtered by draft status)
-    let all_posts = state.post_cache.read().clone();
-
-    // Filter by author if provided
-    let mut filtered_posts: Vec<_> = if let Some(ref author_filter) = author {
-        all_posts
-            .into_iter()
-            .filter(|p| p.author().map(|a| a == author_filter).unwrap_or(false))
-            .collect()
+    query.page = Some(page);
+    list(state, Query(query)).await
+}
+
+/// URL of a listing with the author/category/tag filters of `query`
+pub(crate) fn listing_url(path: &str, query: &ListQuery) -> String {
+    let filters = query.filter_query_string();
+    if filters.is_empty() {
+        path.to_string()
     } else {
-        all_posts
-    };
+        format!("{}?{}", path, filters)
+    }
+}
 
-    // Filter by category if provided
-    if let Some(ref cat_filter) = category {
-        filtered_posts = filtered_posts
-            .into_iter()
-            .filter(|p| {
-                p.frontmatter
-                    .category
-                    .as_ref()
-                    .map(|c| c == cat_filter)
-                    .unwrap_or(false)
-            })
-            .collect();
+/// Prefix for pagination links, so that `{page_url}2` keeps the filters
+///
+/// Unfiltered listings use the path form, e.g. `/tags/rust/page/2`; filtered
+/// ones fall back to the query string, e.g. `/tags/rust?author=claude&page=2`.
+pub(crate) fn page_url(path: &str, query: &ListQuery) -> String {
+    if query.filter_query_string().is_empty() {
+        format!("{}/page/", path)
+    } else {
+        format!("{}&page=", listing_url(path, query))
     }
+}
+
+/// Render a paginated listing of the posts matching `query`
+pub(crate) async fn render_post_list(
+    state: Arc<AppState>,
+    page: usize,
+    query: ListQuery,
+    page_url: String,
+) -> Result<Html<String>, StatusCode> {
+    // Load from cache (already filtered by draft status)
+    let content = state.content();
+    let filtered_posts: Vec<_> = content
+        .posts
+        .iter()
+        .filter(|p| query.matches(p))
+        .cloned()
+        .collect();
 
     let per_page = state.config.posts_per_page;
-    let total_pages = (filtered_posts.len() + per_page - 1) / per_page;
+    let total_pages = filtered_posts.len().div_ceil(per_page);
     let skip = (page - 1) * per_page;
 
     let posts: Vec<_> = filtered_posts
@@ -83,20 +233,25 @@ async fn render_post_list(
         .take(per_page)
         .collect();
 
-    let title = if let Some(ref a) = author {
-        format!("{}'s Posts", a)
-    } else if let Some(ref c) = category {
-        format!("{}", blog_content::category_display_name(c))
-    } else {
-        "All Posts".to_string()
+    let registry = &content.registry;
+    let title = query.title(registry);
+    let categories = &registry.categories;
+    let category = query.category.as_deref().and_then(|slug| categories.get(slug));
+    let author = query
+        .author
+        .as_deref()
+        .and_then(|id| registry.authors.find(id));
+    let post_query = query
+        .timeline_scope(registry)
+        .map(|scope| scope_query_string(&scope))
+        .unwrap_or_default();
+    // Page one of a path-form listing is the listing itself; exports don't
+    // write a `page/1` copy of it
+    let prev_url = match page_url.strip_suffix("/page/") {
+        Some(listing) if page == 2 => listing.to_string(),
+        _ => format!("{}{}", page_url, page - 1),
     };
 
-    // Build categories list for filter badges
-    let categories: Vec<(&str, &str)> = blog_content::CATEGORIES
-        .iter()
-        .copied()
-        .collect();
-
     let mut context = tera::Context::new();
     context.insert("posts", &posts);
     context.insert("page", &page);
@@ -105,10 +260,17 @@ async fn render_post_list(
     context.insert("has_prev", &(page > 1));
     context.insert("next_page", &(page + 1));
     context.insert("prev_page", &(page - 1));
+    context.insert("prev_url", &prev_url);
     context.insert("title", &title);
-    context.insert("author_filter", &author);
-    context.insert("category_filter", &category);
+    context.insert("author_filter", &query.author);
+    context.insert("category_filter", &query.category);
+    context.insert("tag_filter", &query.tag);
+    context.insert("filter_query", &query.filter_query_string());
+    context.insert("page_url", &page_url);
     context.insert("categories", &categories);
+    context.insert("category", &category);
+    context.insert("author", &author);
+    context.insert("post_query", &post_query);
 
     let html = state
         .templates
@@ -125,6 +287,7 @@ async fn render_post_list(
 pub async fn show(
     State(state): State<Arc<AppState>>,
     Path(slug): Path<String>,
+    Query(query): Query<ShowQuery>,
 ) -> Result<Html<String>, StatusCode> {
     // Validate slug to prevent path traversal
     if slug.contains("..") || slug.contains('/') || slug.contains('\\') {
@@ -132,14 +295,15 @@ pub async fn show(
     }
 
     // Find post in cache (already filtered by draft status)
-    let posts = state.post_cache.read();
+    let content = state.content();
+    let posts = &content.posts;
     let post = posts
         .iter()
         .find(|p| p.slug() == slug)
         .ok_or(StatusCode::NOT_FOUND)?
         .clone();
 
-    let rendered = render_post_content(&post);
+    let rendered = content.render_cache.get_or_render(&post);
 
     // Find related posts: explicitly related + similar by tags
     let explicit_related: Vec<RelatedPostData> = post
@@ -156,7 +320,28 @@ pub async fn show(
         })
         .collect();
 
-    let similar_by_tags = post.similar_posts_by_tags(&posts, 3);
+    let similar_by_tags = post.similar_posts_by_tags(posts, 3);
+    let series_nav = content.series.nav_for(&post);
+
+    // Stay within the listing the reader came from, if the post is part of it
+    let timeline = &content.timeline;
+    let (scope, adjacent) = query
+        .from
+        .as_deref()
+        .and_then(parse_scope)
+        .and_then(|scope| {
+            let adjacent = timeline.adjacent(&scope, &slug)?.clone();
+            Some((scope, adjacent))
+        })
+        .unwrap_or_else(|| {
+            let adjacent = timeline
+                .adjacent(&TimelineScope::All, &slug)
+                .cloned()
+                .unwrap_or_default();
+            (TimelineScope::All, adjacent)
+        });
+
+    let listing = ListQuery::for_scope(&scope);
 
     let mut context = tera::Context::new();
     context.insert("post", &post);
@@ -164,6 +349,12 @@ pub async fn show(
     context.insert("title", post.title());
     context.insert("explicit_related", &explicit_related);
     context.insert("similar_by_tags", &similar_by_tags);
+    context.insert("seo", &SeoMeta::for_post(&post, &state.config));
+    context.insert("series_nav", &series_nav);
+    context.insert("adjacent", &adjacent);
+    context.insert("post_query", &scope_query_string(&scope));
+    context.insert("listing_title", &listing.title(&content.registry));
+    context.insert("listing_url", &listing_url("/posts", &listing));
 
     if let Some(ref toc_html) = rendered.toc {
         context.insert("toc", toc_html);
@@ -183,78 +374,111 @@ pub async fn show(
     Ok(Html(html))
 }
 
-/// Render markdown content with syntax highlighting and optional TOC
-pub fn render_post_content(post: &Post) -> RenderedContent {
-    let content = &post.raw_content;
-
-    // Extract TOC if enabled
-    let toc = if post.frontmatter.toc {
-        let entries = extract_toc(content);
-        Some(render_toc(&entries))
-    } else {
-        None
-    };
+/// Generated preview image for a post without a `featured_image`
+pub async fn og_image(
+    State(state): State<Arc<AppState>>,
+    Path(slug): Path<String>,
+) -> Result<impl IntoResponse, StatusCode> {
+    // Validate slug to prevent path traversal
+    if slug.contains("..") || slug.contains('/') || slug.contains('\\') {
+        return Err(StatusCode::BAD_REQUEST);
+    }
 
-    // Parse and render markdown with syntax highlighting
-    let html = render_markdown_with_highlighting(content);
+    let content = state.content();
+    let post = content
+        .posts
+        .iter()
+        .find(|p| p.slug() == slug && p.frontmatter.featured_image.is_none())
+        .ok_or(StatusCode::NOT_FOUND)?
+        .clone();
 
-    RenderedContent { html, toc }
+    // Rasterizing takes long enough to keep it off the async workers
+    let png = tokio::task::spawn_blocking(move || {
+        state
+            .og_images
+            .render(&post, &content.registry, &state.config.site_title)
+    })
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+    .map_err(|e| {
+        tracing::error!("{:#}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(([(header::CONTENT_TYPE, "image/png")], png))
 }
 
-fn render_markdown_with_highlighting(content: &str) -> String {
-    let options = Options::all();
-    let parser = Parser::new_ext(content, options);
-
-    let mut in_code_block = false;
-    let mut code_lang = String::new();
-    let mut code_content = String::new();
-    let mut heading_id = String::new();
-
-    let events: Vec<Event> = parser
-        .flat_map(|event| {
-            match event {
-                Event::Start(Tag::CodeBlock(kind)) => {
-                    in_code_block = true;
-                    code_content.clear();
-                    code_lang = match kind {
-                        CodeBlockKind::Fenced(lang) => lang.to_string(),
-                        CodeBlockKind::Indented => String::new(),
-                    };
-                    vec![]
-                }
-                Event::End(TagEnd::CodeBlock) => {
-                    in_code_block = false;
-                    let highlighted = highlight_code(&code_content, &code_lang);
-                    vec![Event::Html(CowStr::from(highlighted))]
-                }
-                Event::Text(text) if in_code_block => {
-                    code_content.push_str(&text);
-                    vec![]
-                }
-                Event::Start(Tag::Heading { level, id, .. }) => {
-                    // Generate heading ID for anchor links
-                    heading_id = id.map(|s: CowStr| s.to_string()).unwrap_or_default();
-                    vec![Event::Start(Tag::Heading { level, id: None, classes: vec![], attrs: vec![] })]
-                }
-                Event::End(TagEnd::Heading(level)) => {
-                    if heading_id.is_empty() {
-                        vec![Event::End(TagEnd::Heading(level))]
-                    } else {
-                        // Add ID to heading for anchor links
-                        let id = std::mem::take(&mut heading_id);
-                        vec![
-                            Event::Html(CowStr::from(format!("<a id=\"{}\"></a>", id))),
-                            Event::End(TagEnd::Heading(level)),
-                        ]
-                    }
-                }
-                _ => vec![event],
-            }
-        })
-        .collect();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use blog_content::Frontmatter;
 
-    let mut html_output = String::new();
-    pulldown_cmark::html::push_html(&mut html_output, events.into_iter());
+    fn test_post(extra: &str) -> Post {
+        let yaml = format!("title: \"Test\"\nslug: \"test\"\ndate: 2025-01-01\n{}", extra);
+        let frontmatter: Frontmatter = serde_yaml::from_str(&yaml).unwrap();
+        Post::new(frontmatter, String::new(), "test.md".to_string())
+    }
 
-    html_output
+    #[test]
+    fn test_tag_filter_combines_with_author() {
+        let post = test_post("author: \"gudnuf\"\ntags: [\"Web Development\"]\n");
+        let query = ListQuery {
+            tag: Some("web development".to_string()),
+            ..ListQuery::default()
+        };
+        assert!(query.matches(&post));
+
+        let query = ListQuery {
+            author: Some("Claude".to_string()),
+            ..query
+        };
+        assert!(!query.matches(&post));
+    }
+
+    #[test]
+    fn test_author_filter_matches_any_co_author() {
+        let post = test_post("author: [\"Claude\", \"gudnuf\"]\n");
+        for author in ["Claude", "gudnuf"] {
+            let query = ListQuery {
+                author: Some(author.to_string()),
+                ..ListQuery::default()
+            };
+            assert!(query.matches(&post));
+        }
+    }
+
+    #[test]
+    fn test_timeline_scope_round_trip() {
+        let query = ListQuery {
+            author: Some("gudnuf".to_string()),
+            category: Some("devops".to_string()),
+            ..ListQuery::default()
+        };
+        let scope = query.timeline_scope(&Registry::default()).unwrap();
+        assert_eq!(scope, TimelineScope::Author("gudnuf".to_string()));
+        assert_eq!(scope_query_string(&scope), "?from=author%3Agudnuf");
+        assert_eq!(parse_scope("author:gudnuf"), Some(scope));
+
+        assert_eq!(
+            parse_scope("category:devops"),
+            Some(TimelineScope::Category("devops".to_string()))
+        );
+        assert_eq!(parse_scope("tag:rust"), None);
+        assert_eq!(parse_scope("author:"), None);
+        assert_eq!(scope_query_string(&TimelineScope::All), "");
+    }
+
+    #[test]
+    fn test_page_url_keeps_filters() {
+        assert_eq!(page_url("/posts", &ListQuery::default()), "/posts/page/");
+
+        let query = ListQuery {
+            page: Some(3),
+            author: Some("gudnuf".to_string()),
+            tag: Some("Rust".to_string()),
+            ..ListQuery::default()
+        };
+        assert_eq!(page_url("/posts", &query), "/posts?author=gudnuf&tag=rust&page=");
+        assert_eq!(listing_url("/posts", &query), "/posts?author=gudnuf&tag=rust");
+    }
 }
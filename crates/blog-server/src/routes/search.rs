@@ -0,0 +1,109 @@
+//! Full-text search handler
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{Html, IntoResponse, Response},
+};
+use blog_content::Post;
+use serde::{Deserialize, Serialize};
+
+use crate::AppState;
+
+/// Most results shown for a query
+const MAX_RESULTS: usize = 20;
+
+#[derive(Deserialize, Default)]
+pub struct SearchQuery {
+    pub q: Option<String>,
+}
+
+/// A post in the results, shaped like a post so that
+/// `partials/post_list_items.html` can render it
+#[derive(Serialize)]
+struct SearchResult {
+    #[serde(flatten)]
+    post: Post,
+    snippet_html: String,
+}
+
+/// Search posts, returning only the result list for htmx requests other than
+/// history restores
+pub async fn search(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<SearchQuery>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, StatusCode> {
+    let q = query.q.unwrap_or_default();
+    let content = state.content();
+    let hits = content.search_index.search(&q, MAX_RESULTS);
+
+    let results: Vec<_> = hits
+        .into_iter()
+        .filter_map(|hit| {
+            let post = content.posts.iter().find(|p| p.slug() == hit.slug)?;
+            Some(SearchResult {
+                post: post.clone(),
+                snippet_html: hit.snippet_html,
+            })
+        })
+        .collect();
+
+    let mut context = tera::Context::new();
+    context.insert("title", "Search");
+    context.insert("query", q.trim());
+    context.insert("posts", &results);
+
+    // htmx asks for the full page when restoring history it no longer has
+    let fragment =
+        headers.contains_key("hx-request") && !headers.contains_key("hx-history-restore-request");
+    let template = if fragment {
+        "partials/search_results.html"
+    } else {
+        "search.html"
+    };
+
+    let html = state.templates.render(template, &context).map_err(|e| {
+        tracing::error!("Failed to render template: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    // The same URL serves both the page and the fragment, so keep caches apart
+    Ok((
+        [(header::VARY, "HX-Request, HX-History-Restore-Request")],
+        Html(html),
+    ))
+}
+
+/// Serve the client-side search index generated at cache load
+///
+/// Browsers revalidate on every use and get `304 Not Modified` until the
+/// posts change.
+pub async fn index_json(State(state): State<Arc<AppState>>, headers: HeaderMap) -> Response {
+    let index = state.content().search_index_json.clone();
+
+    let cached = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|tags| {
+            tags.split(',')
+                .any(|tag| tag.trim() == index.etag || tag.trim() == "*")
+        });
+    let cache_headers = [
+        (header::ETAG, index.etag),
+        (header::CACHE_CONTROL, "no-cache".to_string()),
+    ];
+
+    if cached {
+        (StatusCode::NOT_MODIFIED, cache_headers).into_response()
+    } else {
+        (
+            cache_headers,
+            [(header::CONTENT_TYPE, "application/json")],
+            index.json,
+        )
+            .into_response()
+    }
+}
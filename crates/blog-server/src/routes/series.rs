@@ -0,0 +1,41 @@
+//! Series landing page handler
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Html,
+};
+
+use crate::AppState;
+
+/// Every post in a series, in reading order
+pub async fn show(
+    State(state): State<Arc<AppState>>,
+    Path(slug): Path<String>,
+) -> Result<Html<String>, StatusCode> {
+    let content = state.content();
+    let series = content.series.get(&slug).ok_or(StatusCode::NOT_FOUND)?;
+
+    let posts: Vec<_> = series
+        .parts
+        .iter()
+        .filter_map(|part| content.posts.iter().find(|p| p.slug() == part.slug))
+        .collect();
+
+    let mut context = tera::Context::new();
+    context.insert("title", &series.name);
+    context.insert("series", series);
+    context.insert("posts", &posts);
+
+    let html = state
+        .templates
+        .render("series.html", &context)
+        .map_err(|e| {
+            tracing::error!("Failed to render template: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Html(html))
+}
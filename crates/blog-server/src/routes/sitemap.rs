@@ -0,0 +1,25 @@
+//! Sitemap and robots.txt route handlers
+
+use std::sync::Arc;
+
+use axum::{extract::State, http::header, response::IntoResponse};
+
+use crate::sitemap::build_robots;
+use crate::AppState;
+
+/// Serve the XML sitemap generated at cache load
+pub async fn sitemap(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let xml = state.content().sitemap.clone();
+    (
+        [(header::CONTENT_TYPE, "application/xml; charset=utf-8")],
+        xml,
+    )
+}
+
+/// Serve robots.txt pointing at the sitemap
+pub async fn robots(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+        build_robots(&state.config),
+    )
+}
@@ -0,0 +1,77 @@
+//! Tag index and tag archive handlers
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{Html, IntoResponse, Redirect, Response},
+};
+
+use crate::routes::posts::{
+    encode_query_value, listing_url, page_url, render_post_list, ListQuery,
+};
+use crate::AppState;
+
+/// List every tag with the number of posts using it
+pub async fn index(State(state): State<Arc<AppState>>) -> Result<Html<String>, StatusCode> {
+    let tags = blog_content::tag_counts(state.content().posts.iter());
+
+    let mut context = tera::Context::new();
+    context.insert("title", "Tags");
+    context.insert("tags", &tags);
+
+    let html = state.templates.render("tags.html", &context).map_err(|e| {
+        tracing::error!("Failed to render template: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Html(html))
+}
+
+/// Paginated listing of the posts with a tag, optionally narrowed further by
+/// author or category
+pub async fn show(
+    State(state): State<Arc<AppState>>,
+    Path(tag): Path<String>,
+    Query(mut query): Query<ListQuery>,
+) -> Result<Response, StatusCode> {
+    // Send variants like `/tags/Web%20Dev` to the canonical `/tags/web-dev`
+    let normalized = blog_content::normalize_tag(&tag);
+    if normalized.is_empty() {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    if normalized != tag {
+        query.tag = None;
+        let url = listing_url(
+            &format!("/tags/{}", encode_query_value(&normalized)),
+            &query,
+        );
+        return Ok(Redirect::permanent(&url).into_response());
+    }
+
+    let exists = state.content().posts.iter().any(|p| p.has_tag(&tag));
+    if !exists {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    // The tag is part of the path, so pagination links only carry the other filters
+    query.tag = None;
+    let url = page_url(&format!("/tags/{}", encode_query_value(&tag)), &query);
+    query.tag = Some(tag);
+
+    let page = query.page.unwrap_or(1).max(1);
+    render_post_list(state, page, query, url)
+        .await
+        .map(IntoResponse::into_response)
+}
+
+/// `/tags/:tag/page/:page`, the path form of `/tags/:tag?page=N`
+pub async fn show_page(
+    state: State<Arc<AppState>>,
+    Path((tag, page)): Path<(String, usize)>,
+    Query(mut query): Query<ListQuery>,
+) -> Result<Response, StatusCode> {
+    query.page = Some(page);
+    show(state, Path(tag), Query(query)).await
+}
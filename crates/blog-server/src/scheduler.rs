@@ -0,0 +1,65 @@
+//! Scheduled publishing
+//!
+//! Posts with a `date` or `publish_at` in the future are kept out of the post
+//! cache until that time arrives in the configured timezone. A timer checks
+//! for posts that have come due and regenerates listings, feeds, search and
+//! the sitemap when any have, without waiting for a reload.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::NaiveDateTime;
+use tokio::time::MissedTickBehavior;
+
+use crate::live_reload::ReloadEvent;
+use crate::AppState;
+
+/// How often to look for posts that have come due
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Spawn a task that publishes scheduled posts as they come due
+pub fn spawn_scheduler(state: Arc<AppState>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let mut last_check = state.config.now();
+
+        loop {
+            interval.tick().await;
+            let now = state.config.now();
+            let due = due_posts(&state, last_check, now);
+            last_check = now;
+
+            if due.is_empty() {
+                continue;
+            }
+
+            tracing::info!("Publishing scheduled post(s): {}", due.join(", "));
+            {
+                let _reload = state.reload_lock.lock();
+                crate::replace_post_cache(&state, crate::all_posts(&state));
+            }
+            state.live_reload.notify(ReloadEvent::Posts(due));
+        }
+    });
+}
+
+/// Slugs of posts that became visible by `now`
+///
+/// That is every post still held back whose time has passed, plus, when
+/// drafts are shown, cached posts whose time passed since the last check:
+/// those are listed early but left out of feeds, search and the sitemap
+/// until then.
+fn due_posts(state: &AppState, last_check: NaiveDateTime, now: NaiveDateTime) -> Vec<String> {
+    let content = state.content();
+
+    let held = content.scheduled.iter().filter(|p| !p.is_scheduled_at(now));
+    let previewed = content
+        .posts
+        .iter()
+        .filter(|p| !p.is_draft() && p.is_scheduled_at(last_check) && !p.is_scheduled_at(now));
+
+    held.chain(previewed)
+        .map(|p| p.slug().to_string())
+        .collect()
+}
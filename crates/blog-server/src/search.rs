@@ -0,0 +1,71 @@
+//! Client-side search index artifact
+
+use blog_content::cache::ContentHasher;
+use blog_content::{ClientIndex, Post};
+use chrono::NaiveDateTime;
+
+/// `/search-index.json`, pre-serialized along with its ETag
+#[derive(Debug, Clone, Default)]
+pub struct SearchIndexJson {
+    pub json: String,
+    /// Quoted hash of `json`, so it only changes when the index does
+    pub etag: String,
+}
+
+impl SearchIndexJson {
+    /// Serialize the client index for every published post
+    ///
+    /// Drafts and posts scheduled after `now` are never included, even when
+    /// the server is configured to show them, since the file is meant to be
+    /// cached and exported.
+    pub fn build(posts: &[Post], now: NaiveDateTime) -> Self {
+        let index = ClientIndex::build(
+            posts
+                .iter()
+                .filter(|p| !p.is_draft() && !p.is_scheduled_at(now)),
+        );
+        let json = serde_json::to_string(&index).unwrap_or_else(|e| {
+            tracing::error!("Failed to serialize search index: {}", e);
+            String::from("{}")
+        });
+
+        let mut hasher = ContentHasher::new();
+        hasher.write_str(&json);
+        let etag = format!("\"{:016x}\"", hasher.finish());
+
+        Self { json, etag }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use blog_content::Frontmatter;
+
+    fn test_post(slug: &str, draft: bool) -> Post {
+        let yaml = format!(
+            "title: \"{}\"\nslug: \"{}\"\ndate: 2025-01-01\ndraft: {}\n",
+            slug, slug, draft
+        );
+        let frontmatter: Frontmatter = serde_yaml::from_str(&yaml).unwrap();
+        Post::new(frontmatter, "Body text".to_string(), format!("{}.md", slug))
+    }
+
+    #[test]
+    fn test_etag_follows_content() {
+        let now = NaiveDateTime::parse_from_str("2025-06-01 12:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        let mut scheduled = test_post("scheduled", false);
+        scheduled.frontmatter.publish_at = Some(now + chrono::Duration::minutes(1));
+        let posts = vec![test_post("published", false), test_post("draft", true), scheduled];
+
+        let first = SearchIndexJson::build(&posts, now);
+        assert!(first.json.contains("\"slug\":\"published\""));
+        assert!(!first.json.contains("\"draft\""));
+        assert!(!first.json.contains("\"scheduled\""));
+        assert_eq!(first.etag, SearchIndexJson::build(&posts, now).etag);
+
+        let mut renamed = posts.clone();
+        renamed[0].frontmatter.title = "Renamed".to_string();
+        assert_ne!(first.etag, SearchIndexJson::build(&renamed, now).etag);
+    }
+}
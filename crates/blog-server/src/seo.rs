@@ -0,0 +1,262 @@
+//! Head metadata for link previews and search engines
+//!
+//! Handlers put a [`SeoMeta`] in the template context as `seo`, and
+//! `partials/seo.html` turns it into canonical, OpenGraph, Twitter card and
+//! JSON-LD tags.
+
+use blog_content::{Page, Post};
+use serde::Serialize;
+use serde_json::json;
+
+use crate::config::Config;
+use crate::feeds::{rfc3339, updated};
+use crate::routes::posts::encode_query_value;
+
+/// Words of post text used as the description when the frontmatter has none
+const DESCRIPTION_WORDS: usize = 30;
+
+/// Everything `partials/seo.html` renders for one page
+#[derive(Debug, Clone, Serialize)]
+pub struct SeoMeta {
+    pub canonical_url: String,
+    /// `og:type`: `article` for posts, `website` for everything else
+    pub og_type: &'static str,
+    pub title: String,
+    pub description: String,
+    /// Absolute preview image URL
+    pub image: Option<String>,
+    /// `summary_large_image` when there is an image, otherwise `summary`
+    pub twitter_card: &'static str,
+    /// RFC 3339 publish time, for articles
+    pub published_time: Option<String>,
+    /// RFC 3339 time of the last update, for articles
+    pub modified_time: Option<String>,
+    /// Profile URLs of registered authors, names of the rest
+    pub authors: Vec<String>,
+    pub tags: Vec<String>,
+    /// schema.org JSON-LD, already escaped for a `<script>` element
+    pub json_ld: Option<String>,
+}
+
+impl SeoMeta {
+    /// Metadata for a post, with a `BlogPosting` JSON-LD description
+    pub fn for_post(post: &Post, config: &Config) -> Self {
+        let canonical_url =
+            config.absolute_url(&format!("/posts/{}", encode_query_value(post.slug())));
+        let description = post.frontmatter.description.clone().unwrap_or_else(|| {
+            blog_content::excerpt(
+                &blog_content::plain_text(&post.raw_content),
+                DESCRIPTION_WORDS,
+            )
+        });
+        // Posts without an image of their own get a generated one
+        let image = Some(match post.frontmatter.featured_image.as_deref() {
+            Some(image) => absolute(image, config),
+            None => config.absolute_url(&format!(
+                "/posts/{}/og.png",
+                encode_query_value(post.slug())
+            )),
+        });
+        let published = rfc3339(post.date(), config);
+        let modified = rfc3339(updated(post), config);
+
+        let author_urls = post
+            .authors
+            .iter()
+            .map(|author| {
+                author.registered.then(|| {
+                    config.absolute_url(&format!("/authors/{}", encode_query_value(&author.id)))
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let json_ld = json!({
+            "@context": "https://schema.org",
+            "@type": "BlogPosting",
+            "headline": post.title(),
+            "description": description,
+            "url": canonical_url,
+            "mainEntityOfPage": canonical_url,
+            "datePublished": published,
+            "dateModified": modified,
+            "inLanguage": config.site_language,
+            "keywords": post.frontmatter.tags,
+            "image": image,
+            "author": post
+                .authors
+                .iter()
+                .zip(&author_urls)
+                .map(|(author, url)| json!({
+                    "@type": "Person",
+                    "name": author.name,
+                    "url": url,
+                }))
+                .collect::<Vec<_>>(),
+            "publisher": {
+                "@type": "Organization",
+                "name": config.site_title,
+                "url": config.absolute_url("/"),
+            },
+        });
+
+        Self {
+            og_type: "article",
+            title: post.title().to_string(),
+            twitter_card: twitter_card(&image),
+            published_time: Some(published),
+            modified_time: Some(modified),
+            authors: post
+                .authors
+                .iter()
+                .zip(author_urls)
+                .map(|(author, url)| url.unwrap_or_else(|| author.name.clone()))
+                .collect(),
+            tags: post.frontmatter.tags.clone(),
+            json_ld: Some(script_json(&without_nulls(json_ld))),
+            canonical_url,
+            description,
+            image,
+        }
+    }
+
+    /// Metadata for a static page
+    pub fn for_page(page: &Page, config: &Config) -> Self {
+        Self {
+            canonical_url: config
+                .absolute_url(&format!("/pages/{}", encode_query_value(&page.slug))),
+            og_type: "website",
+            title: page.title.clone(),
+            description: blog_content::excerpt(
+                &blog_content::plain_text(&page.raw_content),
+                DESCRIPTION_WORDS,
+            ),
+            image: None,
+            twitter_card: twitter_card(&None),
+            published_time: None,
+            modified_time: None,
+            authors: Vec::new(),
+            tags: Vec::new(),
+            json_ld: None,
+        }
+    }
+}
+
+/// An image URL as given, or resolved against the base URL if it is a path
+fn absolute(url: &str, config: &Config) -> String {
+    if url.starts_with("https://") || url.starts_with("http://") {
+        url.to_string()
+    } else {
+        config.absolute_url(url)
+    }
+}
+
+fn twitter_card(image: &Option<String>) -> &'static str {
+    if image.is_some() {
+        "summary_large_image"
+    } else {
+        "summary"
+    }
+}
+
+/// Drop `null` members so optional fields are left out rather than empty
+fn without_nulls(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => map
+            .into_iter()
+            .filter(|(_, v)| !v.is_null())
+            .map(|(k, v)| (k, without_nulls(v)))
+            .collect(),
+        serde_json::Value::Array(items) => items.into_iter().map(without_nulls).collect(),
+        other => other,
+    }
+}
+
+/// Serialize JSON for inline `<script>` use
+///
+/// `<`, `>` and `&` are written as unicode escapes, so post text such as
+/// `</script>` cannot end the element early.
+fn script_json(value: &serde_json::Value) -> String {
+    value
+        .to_string()
+        .replace('<', "\\u003c")
+        .replace('>', "\\u003e")
+        .replace('&', "\\u0026")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use blog_content::{Frontmatter, Registry};
+
+    fn test_config() -> Config {
+        Config {
+            base_url: "https://example.com".to_string(),
+            ..Config::default()
+        }
+    }
+
+    fn test_post(extra: &str) -> Post {
+        let yaml = format!(
+            "title: \"Hello </script>\"\nslug: hello\ndate: 2025-01-02\nauthor: [claude, Guest]\ntags: [rust]\n{}",
+            extra
+        );
+        let frontmatter: Frontmatter = serde_yaml::from_str(&yaml).unwrap();
+        let mut posts = vec![Post::new(
+            frontmatter,
+            "Some *body* text.".to_string(),
+            "hello.md".to_string(),
+        )];
+        let registry = Registry {
+            authors: serde_yaml::from_str("- id: claude\n  name: Claude\n").unwrap(),
+            ..Registry::default()
+        };
+        registry.apply(&mut posts);
+        posts.remove(0)
+    }
+
+    #[test]
+    fn test_post_metadata() {
+        let seo = SeoMeta::for_post(
+            &test_post("featured_image: /images/hello.png\n"),
+            &test_config(),
+        );
+
+        assert_eq!(seo.canonical_url, "https://example.com/posts/hello");
+        assert_eq!(seo.description, "Some body text.");
+        assert_eq!(
+            seo.image.as_deref(),
+            Some("https://example.com/images/hello.png")
+        );
+        assert_eq!(seo.twitter_card, "summary_large_image");
+        assert_eq!(
+            seo.published_time.as_deref(),
+            Some("2025-01-02T00:00:00+00:00")
+        );
+        assert_eq!(
+            seo.authors,
+            vec!["https://example.com/authors/claude", "Guest"]
+        );
+
+        let json_ld = seo.json_ld.unwrap();
+        assert!(!json_ld.contains("</script>"));
+        let parsed: serde_json::Value = serde_json::from_str(&json_ld).unwrap();
+        assert_eq!(parsed["@type"], "BlogPosting");
+        assert_eq!(parsed["headline"], "Hello </script>");
+        assert_eq!(
+            parsed["author"][0]["url"],
+            "https://example.com/authors/claude"
+        );
+        assert!(parsed["author"][1].get("url").is_none());
+    }
+
+    #[test]
+    fn test_post_without_image() {
+        let seo = SeoMeta::for_post(&test_post("description: Short\n"), &test_config());
+        assert_eq!(seo.description, "Short");
+        assert_eq!(
+            seo.image.as_deref(),
+            Some("https://example.com/posts/hello/og.png")
+        );
+        assert_eq!(seo.twitter_card, "summary_large_image");
+    }
+}
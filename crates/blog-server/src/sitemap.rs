@@ -0,0 +1,241 @@
+//! XML sitemap and robots.txt generation
+
+use blog_content::{Page, Post, Registry};
+use chrono::NaiveDate;
+
+use crate::config::Config;
+use crate::feeds::escape_xml;
+use crate::routes::posts::encode_query_value;
+
+/// A single `<url>` entry in the sitemap
+struct SitemapUrl {
+    path: String,
+    lastmod: Option<NaiveDate>,
+}
+
+/// Build an XML sitemap covering the index, posts, pages and listings
+///
+/// Author, category, tag and series landing pages are listed when they have
+/// posts.
+///
+/// Drafts and posts scheduled for later are never listed, even when the
+/// server is configured to show them.
+pub fn build_sitemap(
+    posts: &[Post],
+    pages: &[Page],
+    registry: &Registry,
+    config: &Config,
+) -> String {
+    let base_url = config.base_url.trim_end_matches('/');
+    let now = config.now();
+    let published: Vec<_> = posts
+        .iter()
+        .filter(|p| !p.is_draft() && !p.is_scheduled_at(now))
+        .collect();
+    let newest = published.iter().map(|p| lastmod(p)).max();
+
+    let mut urls = vec![
+        SitemapUrl {
+            path: "/".to_string(),
+            lastmod: newest,
+        },
+        SitemapUrl {
+            path: "/posts".to_string(),
+            lastmod: newest,
+        },
+    ];
+
+    urls.extend(published.iter().map(|post| SitemapUrl {
+        path: format!("/posts/{}", post.slug()),
+        lastmod: Some(lastmod(post)),
+    }));
+
+    urls.extend(pages.iter().map(|page| SitemapUrl {
+        path: format!("/pages/{}", page.slug),
+        lastmod: None,
+    }));
+
+    if !registry.authors.is_empty() {
+        urls.push(SitemapUrl {
+            path: "/authors".to_string(),
+            lastmod: newest,
+        });
+    }
+    urls.extend(registry.authors.iter().filter_map(|author| {
+        landing_url(
+            &published,
+            format!("/authors/{}", encode_query_value(&author.id)),
+            |post| post.authors.iter().any(|a| a.registered && a.id == author.id),
+        )
+    }));
+
+    urls.extend(registry.categories.iter().filter_map(|category| {
+        landing_url(
+            &published,
+            format!("/categories/{}", encode_query_value(&category.slug)),
+            |post| post.frontmatter.category.as_ref() == Some(&category.slug),
+        )
+    }));
+
+    let tag_counts = blog_content::tag_counts(published.iter().copied());
+    if !tag_counts.is_empty() {
+        urls.push(SitemapUrl {
+            path: "/tags".to_string(),
+            lastmod: newest,
+        });
+    }
+    urls.extend(tag_counts.into_iter().filter_map(|entry| {
+        landing_url(
+            &published,
+            format!("/tags/{}", encode_query_value(&entry.tag)),
+            |post| post.has_tag(&entry.tag),
+        )
+    }));
+
+    let series = blog_content::SeriesIndex::build(published.iter().copied());
+    urls.extend(series.iter().filter_map(|series| {
+        landing_url(
+            &published,
+            format!("/series/{}", encode_query_value(&series.slug)),
+            |post| series.position(post.slug()).is_some(),
+        )
+    }));
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n");
+
+    for url in urls {
+        xml.push_str("<url>\n");
+        xml.push_str(&format!(
+            "<loc>{}{}</loc>\n",
+            escape_xml(base_url),
+            escape_xml(&url.path)
+        ));
+        if let Some(lastmod) = url.lastmod {
+            xml.push_str(&format!(
+                "<lastmod>{}</lastmod>\n",
+                lastmod.format("%Y-%m-%d")
+            ));
+        }
+        xml.push_str("</url>\n");
+    }
+
+    xml.push_str("</urlset>\n");
+
+    xml
+}
+
+/// Build robots.txt pointing crawlers at the sitemap
+pub fn build_robots(config: &Config) -> String {
+    format!(
+        "User-agent: *\nAllow: /\n\nSitemap: {}\n",
+        config.absolute_url("/sitemap.xml")
+    )
+}
+
+/// Sitemap entry for a landing page, dated by its newest post, if it has any
+fn landing_url(posts: &[&Post], path: String, filter: impl Fn(&Post) -> bool) -> Option<SitemapUrl> {
+    posts
+        .iter()
+        .filter(|p| filter(p))
+        .map(|p| lastmod(p))
+        .max()
+        .map(|lastmod| SitemapUrl {
+            path,
+            lastmod: Some(lastmod),
+        })
+}
+
+/// Last modification date, by the same rule as the feeds' `updated`
+fn lastmod(post: &Post) -> NaiveDate {
+    crate::feeds::updated(post).date()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use blog_content::{Author, Authors, Categories, Category, Frontmatter};
+
+    fn test_post(slug: &str, extra: &str) -> Post {
+        let yaml = format!(
+            "title: \"{}\"\nslug: \"{}\"\ndate: 2025-01-02 10:00:00\n{}",
+            slug, slug, extra
+        );
+        let frontmatter: Frontmatter = serde_yaml::from_str(&yaml).unwrap();
+        Post::new(frontmatter, String::new(), format!("{}.md", slug))
+    }
+
+    #[test]
+    fn test_sitemap_entries() {
+        let posts = vec![
+            test_post(
+                "updated",
+                "author: \"Claude\"\ncategory: \"devops\"\nupdated: 2025-03-04\n",
+            ),
+            test_post("plain", "author: \"gudnuf\"\ntags: [\"Rust\"]\n"),
+            test_post("backdated", "updated: 2024-12-31\n"),
+            test_post("secret", "draft: true\ncategory: \"hidden\"\n"),
+        ];
+        let pages = vec![Page {
+            title: "About".to_string(),
+            slug: "about".to_string(),
+            template: "page".to_string(),
+            toc: false,
+            raw_content: String::new(),
+            file_path: "about.md".to_string(),
+        }];
+        let config = Config {
+            base_url: "https://example.com".to_string(),
+            ..Config::default()
+        };
+
+        let registry = Registry {
+            categories: Categories::new(vec![Category {
+                slug: "devops".to_string(),
+                name: "DevOps".to_string(),
+                description: None,
+                color: None,
+                icon: None,
+            }]),
+            authors: Authors::new(vec![Author {
+                id: "claude".to_string(),
+                registered: true,
+                ..Author::unregistered("Claude")
+            }]),
+            ..Registry::default()
+        };
+        let mut posts = posts;
+        registry.apply(&mut posts);
+
+        let xml = build_sitemap(&posts, &pages, &registry, &config);
+        assert!(xml.contains("<loc>https://example.com/</loc>"));
+        assert!(xml.contains(
+            "<loc>https://example.com/posts/updated</loc>\n<lastmod>2025-03-04</lastmod>"
+        ));
+        assert!(xml
+            .contains("<loc>https://example.com/posts/plain</loc>\n<lastmod>2025-01-02</lastmod>"));
+        assert!(xml.contains(
+            "<loc>https://example.com/posts/backdated</loc>\n<lastmod>2025-01-02</lastmod>"
+        ));
+        assert!(xml.contains("<loc>https://example.com/pages/about</loc>"));
+        assert!(xml.contains("<loc>https://example.com/authors/claude</loc>"));
+        assert!(!xml.contains("gudnuf"));
+        assert!(xml.contains(
+            "<loc>https://example.com/categories/devops</loc>\n<lastmod>2025-03-04</lastmod>"
+        ));
+        assert!(xml.contains("<loc>https://example.com/tags</loc>"));
+        assert!(xml.contains("<loc>https://example.com/tags/rust</loc>"));
+        assert!(!xml.contains("secret"));
+        assert!(!xml.contains("hidden"));
+    }
+
+    #[test]
+    fn test_robots_points_at_sitemap() {
+        let config = Config {
+            base_url: "https://example.com/".to_string(),
+            ..Config::default()
+        };
+
+        assert!(build_robots(&config).contains("Sitemap: https://example.com/sitemap.xml"));
+    }
+}
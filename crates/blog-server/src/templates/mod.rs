@@ -1,43 +1,89 @@
 //! Tera template management
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::RwLock;
 
-use chrono::{Datelike, Local, NaiveDateTime};
+use chrono::{Datelike, NaiveDateTime, Utc};
+use chrono_tz::Tz;
 use tera::Tera;
 
 /// Template manager wrapping Tera
 pub struct Templates {
     tera: RwLock<Tera>,
+    templates_path: PathBuf,
+    /// Timezone "now" is read in by `relative_time` and `current_year`
+    timezone: Tz,
+    globals: RwLock<tera::Context>,
 }
 
 impl Templates {
-    /// Create a new template manager loading templates from the given path
-    pub fn new(templates_path: &Path) -> anyhow::Result<Self> {
-        let pattern = templates_path.join("**/*.html");
-        let pattern_str = pattern.to_string_lossy();
-
-        let mut tera = Tera::new(&pattern_str)?;
-
-        // Register custom filters
-        tera.register_filter("date_format", date_format_filter);
-        tera.register_filter("relative_time", relative_time_filter);
-
-        // Register custom functions
-        tera.register_function("current_year", current_year_function);
-
+    /// Create a new template manager loading templates from the given path,
+    /// with dates relative to the current time in `timezone`
+    pub fn new(templates_path: &Path, timezone: Tz) -> anyhow::Result<Self> {
         Ok(Self {
-            tera: RwLock::new(tera),
+            tera: RwLock::new(load_tera(templates_path, timezone)?),
+            templates_path: templates_path.to_path_buf(),
+            timezone,
+            globals: RwLock::new(tera::Context::new()),
         })
     }
 
+    /// Add a value that is available in every rendered template
+    ///
+    /// Handler contexts take precedence over globals with the same name.
+    /// Inserting a name again replaces its value for later renders.
+    pub fn insert_global<T: serde::Serialize + ?Sized>(&self, key: &str, value: &T) {
+        self.globals.write().unwrap().insert(key, value);
+    }
+
+    /// Re-read every template from disk
+    ///
+    /// The new set is parsed in full before it is swapped in, so on error the
+    /// previously loaded templates stay in place.
+    pub fn reload(&self) -> anyhow::Result<()> {
+        let tera = load_tera(&self.templates_path, self.timezone)?;
+        *self.tera.write().unwrap() = tera;
+        Ok(())
+    }
+
     /// Render a template with the given context
     pub fn render(&self, template: &str, context: &tera::Context) -> anyhow::Result<String> {
+        let mut full_context = self.globals.read().unwrap().clone();
+        full_context.extend(context.clone());
+
         let tera = self.tera.read().unwrap();
-        Ok(tera.render(template, context)?)
+        Ok(tera.render(template, &full_context)?)
     }
 }
 
+/// Load templates from a directory and register custom filters and functions
+fn load_tera(templates_path: &Path, timezone: Tz) -> anyhow::Result<Tera> {
+    let pattern = templates_path.join("**/*.html");
+    let pattern_str = pattern.to_string_lossy();
+
+    let mut tera = Tera::new(&pattern_str)?;
+
+    // Register custom filters
+    tera.register_filter("date_format", date_format_filter);
+    tera.register_filter(
+        "relative_time",
+        move |value: &tera::Value, args: &std::collections::HashMap<String, tera::Value>| {
+            relative_time_filter(value, args, now_in(timezone))
+        },
+    );
+    tera.register_filter("path_segment", path_segment_filter);
+
+    // Register custom functions
+    tera.register_function(
+        "current_year",
+        move |_args: &std::collections::HashMap<String, tera::Value>| {
+            Ok(tera::Value::Number(now_in(timezone).year().into()))
+        },
+    );
+
+    Ok(tera)
+}
+
 /// Custom filter for formatting dates (handles both date and datetime)
 fn date_format_filter(
     value: &tera::Value,
@@ -65,18 +111,34 @@ fn date_format_filter(
     }
 }
 
-/// Custom function to get the current year
-fn current_year_function(
+/// Custom filter percent-encoding a value for use as one URL path segment
+///
+/// Unlike Tera's `urlencode`, `/` is encoded too, so an id such as `ci/cd`
+/// cannot add a segment.
+fn path_segment_filter(
+    value: &tera::Value,
     _args: &std::collections::HashMap<String, tera::Value>,
 ) -> tera::Result<tera::Value> {
-    let year = chrono::Local::now().year();
-    Ok(tera::Value::Number(year.into()))
+    let segment = value
+        .as_str()
+        .ok_or_else(|| tera::Error::msg("path_segment filter expects a string"))?;
+
+    Ok(tera::Value::String(
+        crate::routes::posts::encode_query_value(segment),
+    ))
+}
+
+/// Current wall-clock time in `timezone`, comparable with post dates
+fn now_in(timezone: Tz) -> NaiveDateTime {
+    Utc::now().with_timezone(&timezone).naive_local()
 }
 
 /// Custom filter for displaying relative time (e.g., "2 hours ago", "yesterday")
+/// as of `now`
 fn relative_time_filter(
     value: &tera::Value,
     _args: &std::collections::HashMap<String, tera::Value>,
+    now: NaiveDateTime,
 ) -> tera::Result<tera::Value> {
     let datetime_str = value
         .as_str()
@@ -93,7 +155,6 @@ fn relative_time_filter(
         return Ok(value.clone());
     };
 
-    let now = Local::now().naive_local();
     let duration = now.signed_duration_since(datetime);
     let seconds = duration.num_seconds();
 
@@ -0,0 +1,214 @@
+//! Filesystem watcher for live content and template reload
+//!
+//! Enabled with `BLOG_WATCH=true`. Changes are debounced so that an editor
+//! saving several files (or writing a file in several steps) triggers a single
+//! reload, and only the posts that were touched are re-read and re-rendered.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use blog_content::{Authors, Categories};
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use tokio::sync::mpsc;
+
+use crate::live_reload::ReloadEvent;
+use crate::AppState;
+
+/// Quiet period to wait for after the last filesystem event before reloading
+const DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// Directories being watched, canonicalized so event paths can be classified
+struct WatchedDirs {
+    content: PathBuf,
+    /// `None` when the directory does not exist
+    posts: Option<PathBuf>,
+    /// `None` when the directory does not exist
+    pages: Option<PathBuf>,
+    templates: PathBuf,
+    /// The posts directory as posts were loaded from it, so that changed files
+    /// can be matched to cached posts by path
+    posts_source: PathBuf,
+}
+
+/// Changes collected during one debounce window
+#[derive(Debug, Default)]
+struct PendingChanges {
+    posts: HashSet<PathBuf>,
+    pages: HashSet<PathBuf>,
+    templates: bool,
+    registry: bool,
+}
+
+impl PendingChanges {
+    /// Sort the paths of a filesystem event into posts, pages, templates and
+    /// registry data files
+    fn record(&mut self, event: Event, dirs: &WatchedDirs) {
+        if matches!(event.kind, EventKind::Access(_)) {
+            return;
+        }
+
+        for path in event.paths {
+            let post = within(&path, &dirs.posts).filter(|_| has_extension(&path, "md"));
+            if let Some(relative) = post {
+                self.posts.insert(dirs.posts_source.join(relative));
+            } else if within(&path, &dirs.pages).is_some() && has_extension(&path, "md") {
+                self.pages.insert(path);
+            } else if path.starts_with(&dirs.templates) && has_extension(&path, "html") {
+                self.templates = true;
+            } else if path == dirs.content.join(Categories::FILE_NAME)
+                || path == dirs.content.join(Authors::FILE_NAME)
+            {
+                self.registry = true;
+            }
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.posts.is_empty() && self.pages.is_empty() && !self.templates && !self.registry
+    }
+}
+
+/// Start watching content and templates, reloading the caches on change
+pub fn spawn_watcher(state: Arc<AppState>) -> anyhow::Result<()> {
+    let posts_source = state.config.content_path.join("posts");
+    let dirs = WatchedDirs {
+        content: state.config.content_path.canonicalize()?,
+        posts: existing_dir(&posts_source),
+        pages: existing_dir(&state.config.content_path.join("pages")),
+        templates: state.config.templates_path.canonicalize()?,
+        posts_source,
+    };
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<Event>| match res {
+        Ok(event) => {
+            // The receiver only goes away when the server shuts down
+            let _ = tx.send(event);
+        }
+        Err(e) => tracing::warn!("File watcher error: {}", e),
+    })?;
+
+    let flat_dirs: Vec<_> = std::iter::once(&dirs.content)
+        .chain(&dirs.posts)
+        .chain(&dirs.pages)
+        .collect();
+    for dir in &flat_dirs {
+        watcher.watch(dir, RecursiveMode::NonRecursive)?;
+    }
+    watcher.watch(&dirs.templates, RecursiveMode::Recursive)?;
+
+    tracing::info!(
+        "Watching {:?} and {:?} for changes",
+        flat_dirs,
+        dirs.templates
+    );
+
+    tokio::spawn(async move {
+        // Dropping the watcher stops it, so keep it alive for the task's lifetime
+        let _watcher = watcher;
+
+        while let Some(event) = rx.recv().await {
+            let mut changes = PendingChanges::default();
+            changes.record(event, &dirs);
+
+            // Keep collecting until the filesystem has been quiet for a while
+            loop {
+                match tokio::time::timeout(DEBOUNCE, rx.recv()).await {
+                    Ok(Some(event)) => changes.record(event, &dirs),
+                    Ok(None) => return,
+                    Err(_) => break,
+                }
+            }
+
+            if !changes.is_empty() {
+                apply_changes(&state, changes);
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Reload whatever a batch of changes touched and refresh affected browsers
+fn apply_changes(state: &AppState, changes: PendingChanges) {
+    if changes.templates {
+        match state.templates.reload() {
+            Ok(()) => tracing::info!("Templates reloaded"),
+            Err(e) => tracing::error!("Failed to reload templates: {}", e),
+        }
+    }
+
+    // Template, category and author changes can alter every page
+    let reload_all = changes.templates || changes.registry;
+
+    if !changes.posts.is_empty() {
+        let changed: Vec<_> = changes.posts.into_iter().collect();
+        tracing::info!("Reloading {} changed post file(s)", changed.len());
+
+        let reload = state.reload_lock.lock();
+        let mut posts = crate::all_posts(state);
+        let mut slugs = slugs_for_files(&posts, &changed);
+        blog_content::reload_changed_posts(&mut posts, &changed);
+        posts.retain(|p| state.config.enable_drafts || !p.is_draft());
+        slugs.extend(slugs_for_files(&posts, &changed));
+        slugs.sort();
+        slugs.dedup();
+
+        crate::replace_post_cache(state, posts);
+        drop(reload);
+
+        if !reload_all {
+            state.live_reload.notify(ReloadEvent::Posts(slugs));
+        }
+    } else if changes.registry || !changes.pages.is_empty() {
+        // Categories and authors are re-read along with the post cache, and pages are
+        // loaded on demand but listed in the sitemap
+        tracing::info!("Categories, authors or pages changed, regenerating listings");
+        let _reload = state.reload_lock.lock();
+        crate::replace_post_cache(state, crate::all_posts(state));
+    }
+
+    if reload_all {
+        state.live_reload.notify(ReloadEvent::All);
+    } else if !changes.pages.is_empty() {
+        let slugs = changes
+            .pages
+            .iter()
+            .filter_map(|path| path.file_stem())
+            .map(|stem| stem.to_string_lossy().to_string())
+            .collect();
+        state.live_reload.notify(ReloadEvent::Pages(slugs));
+    }
+}
+
+/// Slugs of the cached posts loaded from any of the given files
+fn slugs_for_files(posts: &[blog_content::Post], files: &[PathBuf]) -> Vec<String> {
+    posts
+        .iter()
+        .filter(|post| files.iter().any(|path| Path::new(&post.file_path) == path))
+        .map(|post| post.slug().to_string())
+        .collect()
+}
+
+/// Canonical form of `path`, or `None` if it is not an existing directory
+///
+/// Missing directories are skipped rather than failing the watcher; posts or
+/// pages added to one later are picked up on restart.
+fn existing_dir(path: &Path) -> Option<PathBuf> {
+    if !path.is_dir() {
+        tracing::info!("Not watching {:?}: no such directory", path);
+        return None;
+    }
+    path.canonicalize().ok()
+}
+
+/// `path` relative to `dir`, if it lies inside it
+fn within<'a>(path: &'a Path, dir: &Option<PathBuf>) -> Option<&'a Path> {
+    dir.as_ref().and_then(|dir| path.strip_prefix(dir).ok())
+}
+
+fn has_extension(path: &Path, extension: &str) -> bool {
+    path.extension().is_some_and(|ext| ext == extension)
+}
@@ -0,0 +1,154 @@
+// Offline search over /search-index.json, for builds without a server.
+//
+// Attach to an input with `data-search-index="<url>"` and
+// `data-search-results="<selector>"`. Ranking mirrors the server: every query
+// word must match, whole words beat prefixes, and rarer words count for more.
+(function () {
+  'use strict';
+
+  var PREFIX_FACTOR = 0.5;
+  var MAX_RESULTS = 20;
+
+  function words(text) {
+    return text.toLowerCase().match(/[\p{L}\p{N}]+/gu) || [];
+  }
+
+  function escapeHtml(text) {
+    return text.replace(/[&<>"']/g, function (c) {
+      return { '&': '&amp;', '<': '&lt;', '>': '&gt;', '"': '&quot;', "'": '&#39;' }[c];
+    });
+  }
+
+  // Match words in the raw text and escape each piece, so entity names such
+  // as `amp` in `&amp;` are never marked.
+  function highlight(text, terms) {
+    return text.split(/([\p{L}\p{N}]+)/u).map(function (piece, i) {
+      var escaped = escapeHtml(piece);
+      if (i % 2 === 0) return escaped;
+      var lower = piece.toLowerCase();
+      var hit = terms.some(function (term) { return lower.indexOf(term) === 0; });
+      return hit ? '<mark>' + escaped + '</mark>' : escaped;
+    }).join('');
+  }
+
+  function search(index, query) {
+    var terms = words(query).filter(function (term, i, all) {
+      return all.indexOf(term) === i;
+    });
+    if (!terms.length) return { terms: terms, hits: [] };
+
+    var docCount = index.docs.length;
+    var keys = Object.keys(index.terms);
+    var totals = {};
+
+    terms.forEach(function (term) {
+      var best = {};
+      keys.forEach(function (word) {
+        if (word.indexOf(term) !== 0) return;
+        var postings = index.terms[word];
+        var factor = word === term ? 1 : PREFIX_FACTOR;
+        var idf = Math.log(1 + docCount / postings.length);
+        postings.forEach(function (posting) {
+          var score = Math.log1p(posting[1]) * idf * factor;
+          best[posting[0]] = Math.max(best[posting[0]] || 0, score);
+        });
+      });
+      Object.keys(best).forEach(function (doc) {
+        var total = totals[doc] || (totals[doc] = { score: 0, matched: 0 });
+        total.score += best[doc];
+        total.matched += 1;
+      });
+    });
+
+    var hits = Object.keys(totals)
+      .filter(function (doc) { return totals[doc].matched === terms.length; })
+      .map(function (doc) { return { doc: Number(doc), score: totals[doc].score }; })
+      .sort(function (a, b) { return b.score - a.score || a.doc - b.doc; })
+      .slice(0, MAX_RESULTS);
+
+    return { terms: terms, hits: hits };
+  }
+
+  function render(index, query, results) {
+    var found = search(index, query);
+    if (!found.terms.length) {
+      results.innerHTML = '';
+      return;
+    }
+
+    var html = '<p class="text-sm text-slate-500 mb-4">' + found.hits.length +
+      ' result' + (found.hits.length === 1 ? '' : 's') + ' for &ldquo;' +
+      escapeHtml(query.trim()) + '&rdquo;</p><div class="space-y-4">';
+
+    found.hits.forEach(function (hit) {
+      var doc = index.docs[hit.doc];
+      var heading = doc.headings.find(function (h) {
+        return words(h.text).some(function (w) {
+          return found.terms.some(function (t) { return w.indexOf(t) === 0; });
+        });
+      });
+      var url = '/posts/' + encodeURIComponent(doc.slug);
+
+      html += '<article class="card p-5">' +
+        '<h2 class="text-xl font-semibold mb-2 text-slate-100"><a href="' + url +
+        '" class="hover:text-accent transition-colors">' + escapeHtml(doc.title) + '</a></h2>' +
+        '<div class="text-sm text-slate-500 mb-3"><time datetime="' + doc.date + '">' + doc.date + '</time>' +
+        ' &middot; ' + doc.reading_time + ' min read';
+      if (heading) {
+        html += ' &middot; <a href="' + url + '#' + encodeURIComponent(heading.id) +
+          '" class="hover:text-accent transition-colors">' + highlight(heading.text, found.terms) + '</a>';
+      }
+      html += '</div><p class="text-slate-400 mb-3 search-snippet">' +
+        highlight(doc.excerpt, found.terms) + '</p>';
+      if (doc.tags.length) {
+        html += '<div class="flex flex-wrap gap-2">' + doc.tags.map(function (tag) {
+          return '<a href="/tags/' + encodeURIComponent(tag) + '" class="tag">' + escapeHtml(tag) + '</a>';
+        }).join('') + '</div>';
+      }
+      html += '</article>';
+    });
+
+    results.innerHTML = html + '</div>';
+  }
+
+  function attach(input) {
+    var results = document.querySelector(input.dataset.searchResults);
+    if (!results) return;
+
+    fetch(input.dataset.searchIndex)
+      .then(function (response) { return response.json(); })
+      .then(function (index) {
+        var timer;
+        var update = function () {
+          var url = new URL(window.location.href);
+          if (input.value.trim()) {
+            url.searchParams.set('q', input.value);
+          } else {
+            url.searchParams.delete('q');
+          }
+          window.history.replaceState(null, '', url);
+          render(index, input.value, results);
+        };
+
+        input.addEventListener('input', function () {
+          clearTimeout(timer);
+          timer = setTimeout(update, 150);
+        });
+        input.form && input.form.addEventListener('submit', function (e) {
+          e.preventDefault();
+          update();
+        });
+
+        var initial = new URLSearchParams(window.location.search).get('q');
+        if (initial) {
+          input.value = initial;
+          render(index, initial, results);
+        }
+      })
+      .catch(function (e) {
+        console.error('Search index unavailable', e);
+      });
+  }
+
+  document.querySelectorAll('input[data-search-index]').forEach(attach);
+})();